@@ -0,0 +1,363 @@
+use crate::expression::{Expression, ExpressionOperation, ExpressionValue, GroupingSymbol};
+use crate::polynomial::{Polynomial, Term};
+use crate::rational_number::RationalNumber;
+use std::convert::TryFrom;
+
+impl Expression {
+    /// Takes one step toward an expanded expression, innermost groups first. A step either
+    /// distributes the factors of a term over a group, writes a whole number power of a group as
+    /// repeated multiplication, or multiplies out every term that is a single monomial.
+    pub fn expand_next(&self) -> Option<Expression> {
+        if let Some(e) = next_in_group(self, Expression::expand_next) {
+            return Some(e);
+        }
+
+        let terms = split_terms(self);
+        for (i, term) in terms.iter().enumerate() {
+            if let Some(distributed) = distribute(term) {
+                let mut terms = terms.clone();
+                terms.splice(i..=i, distributed);
+                return Some(join_terms(terms, self.grouping()));
+            }
+        }
+
+        // a negated term such as -x^2 is already multiplied out
+        if self.negated_term().is_some() {
+            return None;
+        }
+        let multiplied: Vec<SignedTerm> = terms
+            .iter()
+            .map(|t| match t.monomial() {
+                Some(m) => SignedTerm::from_monomial(&m),
+                None => t.clone(),
+            })
+            .collect();
+        let e = join_terms(multiplied, self.grouping());
+        if e == *self {
+            None
+        } else {
+            Some(e)
+        }
+    }
+
+    pub fn expand(&self) -> Expression {
+        self.expand_steps().pop().unwrap_or_else(|| self.clone())
+    }
+
+    /// Every intermediate expression `expand_next` passes through, ending with the expanded one.
+    pub fn expand_steps(&self) -> Vec<Expression> {
+        steps(self, Expression::expand_next)
+    }
+
+    /// Combines the first set of like terms, innermost groups first. Terms cancelling to zero are
+    /// dropped. `None` once no like terms are left or their sum does not fit.
+    pub fn collect_like_terms_next(&self) -> Option<Expression> {
+        if let Some(e) = next_in_group(self, Expression::collect_like_terms_next) {
+            return Some(e);
+        }
+
+        let terms = split_terms(self);
+        let monomials: Vec<Option<Term>> = terms.iter().map(|t| t.monomial()).collect();
+        for (i, m) in monomials.iter().enumerate() {
+            let m = match m {
+                Some(m) => m,
+                None => continue,
+            };
+            let like: Vec<usize> = monomials
+                .iter()
+                .enumerate()
+                .skip(i + 1)
+                .filter(|(_, other)| matches!(other, Some(other) if other.variables == m.variables))
+                .map(|(j, _)| j)
+                .collect();
+            if like.is_empty() {
+                continue;
+            }
+
+            let coefficient = like
+                .iter()
+                .try_fold(m.coefficient, |sum, j| {
+                    let other = monomials[*j].as_ref().expect("failed to get like term");
+                    sum.checked_add(&other.coefficient).map(|sum| sum.simplify())
+                })
+                .ok()?;
+            let mut collected = Vec::with_capacity(terms.len() - like.len());
+            for (j, term) in terms.into_iter().enumerate() {
+                if j == i {
+                    if coefficient.numerator != 0 {
+                        collected.push(SignedTerm::from_monomial(&Term {
+                            coefficient,
+                            variables: m.variables.clone(),
+                        }));
+                    }
+                } else if !like.contains(&j) {
+                    collected.push(term);
+                }
+            }
+            return Some(join_terms(collected, self.grouping()));
+        }
+        None
+    }
+
+    pub fn collect_like_terms(&self) -> Expression {
+        self.collect_like_terms_steps().pop().unwrap_or_else(|| self.clone())
+    }
+
+    pub fn collect_like_terms_steps(&self) -> Vec<Expression> {
+        steps(self, Expression::collect_like_terms_next)
+    }
+}
+
+fn steps(e: &Expression, next: fn(&Expression) -> Option<Expression>) -> Vec<Expression> {
+    let mut steps: Vec<Expression> = Vec::new();
+    while let Some(step) = next(steps.last().unwrap_or(e)) {
+        steps.push(step);
+    }
+    steps
+}
+
+// a term of a sum and whether it is subtracted
+#[derive(Clone)]
+struct SignedTerm {
+    negative: bool,
+    values: Vec<ExpressionValue>,
+    operations: Vec<ExpressionOperation>,
+}
+
+impl SignedTerm {
+    fn expression(&self) -> Expression {
+        build(self.values.clone(), self.operations.clone(), GroupingSymbol::Parentheses)
+    }
+
+    // None unless the term is a single monomial, which then carries the term's sign
+    fn monomial(&self) -> Option<Term> {
+        let p = Polynomial::try_from(&self.expression()).ok()?;
+        let mut m = match p.terms().as_slice() {
+            [] => Term {
+                coefficient: RationalNumber::from(0),
+                variables: Vec::new(),
+            },
+            [t] => t.clone(),
+            _ => return None,
+        };
+        if self.negative {
+            m.coefficient = m.coefficient.neg();
+        }
+        Some(m)
+    }
+
+    fn from_monomial(m: &Term) -> SignedTerm {
+        let e = Term {
+            coefficient: m.coefficient.abs(),
+            variables: m.variables.clone(),
+        }
+        .to_expression();
+        SignedTerm {
+            negative: m.coefficient.negative && m.coefficient.numerator != 0,
+            values: e.values().clone(),
+            operations: e.operations().clone(),
+        }
+    }
+}
+
+fn split_terms(e: &Expression) -> Vec<SignedTerm> {
+    let mut values = e.values().iter();
+    let mut terms = vec![SignedTerm {
+        negative: false,
+        values: vec![values.next().expect("failed to get first value").clone()],
+        operations: Vec::new(),
+    }];
+    for (op, val) in e.operations().iter().zip(values) {
+        match op {
+            ExpressionOperation::Addition | ExpressionOperation::Subtraction => terms.push(SignedTerm {
+                negative: *op == ExpressionOperation::Subtraction,
+                values: vec![val.clone()],
+                operations: Vec::new(),
+            }),
+            _ => {
+                let term = terms.last_mut().expect("failed to get term");
+                term.operations.push(op.clone());
+                term.values.push(val.clone());
+            }
+        }
+    }
+    terms
+}
+
+fn join_terms(terms: Vec<SignedTerm>, grouping: GroupingSymbol) -> Expression {
+    let mut values = Vec::new();
+    let mut operations = Vec::new();
+    for (i, mut term) in terms.into_iter().enumerate() {
+        if i > 0 {
+            operations.push(if term.negative {
+                ExpressionOperation::Subtraction
+            } else {
+                ExpressionOperation::Addition
+            });
+        } else if term.negative {
+            // a leading number is only negated when no exponent applies to it, anything else is
+            // grouped with its powers the way the parser reads -x^2
+            match term.values[0] {
+                ExpressionValue::Number(n) if term.operations.first() != Some(&ExpressionOperation::Exponent) => {
+                    term.values[0] = n.neg().into();
+                }
+                _ => {
+                    let powers = term.operations.iter().take_while(|op| **op == ExpressionOperation::Exponent).count();
+                    let mut values = vec![(-1).into()];
+                    values.extend(term.values.drain(..=powers));
+                    let mut operations = vec![ExpressionOperation::Multiplication];
+                    operations.extend(term.operations.drain(..powers));
+                    term.values.insert(0, build(values, operations, GroupingSymbol::Parentheses).into());
+                }
+            }
+        }
+        values.extend(term.values);
+        operations.extend(term.operations);
+    }
+    if values.is_empty() {
+        values.push(0.into());
+    }
+    build(values, operations, grouping)
+}
+
+fn build(values: Vec<ExpressionValue>, operations: Vec<ExpressionOperation>, grouping: GroupingSymbol) -> Expression {
+    let mut values = values.into_iter();
+    let mut e = Expression::new(values.next().expect("failed to get first value"));
+    for (op, val) in operations.into_iter().zip(values) {
+        e = e.push(op, val);
+    }
+    e.set_grouping(grouping)
+}
+
+// applies `next` to the first group that changes
+fn next_in_group(e: &Expression, next: fn(&Expression) -> Option<Expression>) -> Option<Expression> {
+    for (i, val) in e.values().iter().enumerate() {
+        if let ExpressionValue::Expression(group) = val {
+            if let Some(group) = next(group) {
+                let mut values = e.values().clone();
+                values[i] = group.into();
+                return Some(build(values, e.operations().clone(), e.grouping()));
+            }
+        }
+    }
+    None
+}
+
+// -x^2 is a single factor with nothing to distribute, unlike -(x + 1)
+fn is_negated_factor(group: &Expression) -> bool {
+    match group.negated_term() {
+        Some(term) => !matches!(term.values()[0], ExpressionValue::Expression(_)),
+        None => false,
+    }
+}
+
+// a(b + c)d becomes abd + acd and (b + c)^2 becomes (b + c)(b + c)
+fn distribute(term: &SignedTerm) -> Option<Vec<SignedTerm>> {
+    for (i, val) in term.values.iter().enumerate() {
+        let group = match val {
+            ExpressionValue::Expression(group) if !is_negated_factor(group) => group,
+            _ => continue,
+        };
+        let before = if i == 0 { None } else { term.operations.get(i - 1) };
+        if matches!(before, Some(ExpressionOperation::Exponent) | Some(ExpressionOperation::Division)) {
+            continue;
+        }
+
+        if term.operations.get(i) == Some(&ExpressionOperation::Exponent) {
+            let exp = match &term.values[i + 1] {
+                ExpressionValue::Number(n) if n.denominator == 1 && !n.negative => n.numerator,
+                _ => continue,
+            };
+            if term.operations.get(i + 1) == Some(&ExpressionOperation::Exponent) {
+                continue;
+            }
+            let mut values = term.values[..i].to_vec();
+            let mut operations = term.operations[..i].to_vec();
+            if exp == 0 {
+                values.push(1.into());
+            } else {
+                values.push(val.clone());
+                for _ in 1..exp {
+                    operations.push(ExpressionOperation::Multiplication);
+                    values.push(val.clone());
+                }
+            }
+            values.extend_from_slice(&term.values[i + 2..]);
+            operations.extend_from_slice(&term.operations[i + 1..]);
+            return Some(vec![SignedTerm {
+                negative: term.negative,
+                values,
+                operations,
+            }]);
+        }
+
+        let distributed = split_terms(group)
+            .into_iter()
+            .map(|inner| {
+                let mut values = term.values[..i].to_vec();
+                values.extend(inner.values);
+                values.extend_from_slice(&term.values[i + 1..]);
+                let mut operations = term.operations[..i].to_vec();
+                operations.extend(inner.operations);
+                operations.extend_from_slice(&term.operations[i..]);
+                SignedTerm {
+                    negative: term.negative != inner.negative,
+                    values,
+                    operations,
+                }
+            })
+            .collect();
+        return Some(distributed);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_expression;
+
+    fn expand(s: &str) -> String {
+        parse_expression(s).unwrap().expand().to_string()
+    }
+
+    fn collect(s: &str) -> String {
+        parse_expression(s).unwrap().collect_like_terms().to_string()
+    }
+
+    #[test]
+    fn expands_expressions() {
+        let steps: Vec<String> = parse_expression("3(x + 2)")
+            .unwrap()
+            .expand_steps()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(steps, vec!["3 * x + 3 * 2", "3 * x + 6"]);
+
+        assert_eq!(expand("a - 2(b - 3)"), "a - 2 * b + 6");
+        assert_eq!(expand("-(x - 4)"), "-x + 4");
+        assert_eq!(expand("(-x)(x + 1)"), "-x^2 - x");
+        assert_eq!(expand("2(3(y + 1) + 4)"), "6 * y + 6 + 8");
+        assert_eq!(expand("(x + 1)(x - 1)"), "x^2 - x + x - 1");
+        assert_eq!(expand("1/2(x + 1/3)"), "1/2 * x + 1/6");
+        assert_eq!(expand("x -: (x + 1)"), "x -: (x + 1)");
+    }
+
+    #[test]
+    fn collects_like_terms() {
+        let steps: Vec<String> = parse_expression("2x + 5 - x + 1")
+            .unwrap()
+            .collect_like_terms_steps()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(steps, vec!["x + 5 + 1", "x + 6"]);
+
+        assert_eq!(collect("3a + 2b - 3a"), "2 * b");
+        assert_eq!(collect("x - x"), "0");
+        assert_eq!(collect("2(x + x) + y"), "2 * (2 * x) + y");
+        assert_eq!(collect("x^2 + 2"), "x^2 + 2");
+        assert_eq!(collect("4294967295x + x + 1"), "4294967295 * x + x + 1");
+        assert_eq!(parse_expression("(x + 2)^2").unwrap().expand().collect_like_terms().to_string(), "x^2 + 4 * x + 4");
+    }
+}
@@ -0,0 +1,309 @@
+use crate::expression::{Expression, ExpressionOperation, ExpressionValue};
+use crate::formatter::{self, ExpressionFormatter, Parentheses};
+use crate::function::{Function, FunctionCall};
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+
+impl Expression {
+    /// Rewrites the expression in a standard form without evaluating anything: sums and products
+    /// are flattened, so `a - (b - c)` becomes `a - b + c`, their operands are sorted, numbers are
+    /// simplified and only groups that order of operations requires are kept. Positive terms come
+    /// before subtracted ones and constants come last, as in `2 * x + y - 3`.
+    pub fn canonicalize(&self) -> Expression {
+        write(&node(self)).set_grouping(self.grouping())
+    }
+
+    /// The expression with only the parentheses that order of operations requires.
+    pub fn to_minimal_string(&self) -> String {
+        ExpressionFormatter::new().parentheses(Parentheses::Minimal).format(self)
+    }
+
+    /// Whether both expressions have the same canonical form, regardless of how their numbers are
+    /// displayed. `2x + 1/2` and `0.5 + x * 2` are equivalent, but `2(x + 1)` and `2x + 2` are
+    /// not, since nothing is distributed.
+    pub fn is_equivalent(&self, other: &Expression) -> bool {
+        key(&node(self)) == key(&node(other))
+    }
+}
+
+// an expression as a tree, with the operands of sums and products collected together
+#[derive(Clone)]
+enum Node {
+    Leaf(ExpressionValue),
+    Sum(Vec<(bool, Node)>),     // whether each term is subtracted
+    Product(Vec<(bool, Node)>), // whether each factor is divided by
+    Power(Box<Node>, Box<Node>),
+}
+
+fn node(e: &Expression) -> Node {
+    e.fold(
+        &mut |val, _| Ok(leaf(val)),
+        &ExpressionOperation::priority,
+        &mut |op, a, b| Ok(combine(op, a, b)),
+    )
+    .expect("failed to build expression tree")
+}
+
+fn leaf(val: &ExpressionValue) -> Node {
+    match val {
+        ExpressionValue::Expression(e) => node(e),
+        ExpressionValue::Number(n) => Node::Leaf(n.simplify().into()),
+        ExpressionValue::Function(call) => {
+            let mut arguments: Vec<Expression> = call.arguments.iter().map(|a| a.canonicalize()).collect();
+            if matches!(call.function, Function::Min | Function::Max) {
+                arguments.sort_by_cached_key(|a| key(&node(a)));
+            }
+            Node::Leaf(FunctionCall::new(call.function, arguments).into())
+        }
+        val => Node::Leaf(val.clone()),
+    }
+}
+
+fn combine(op: &ExpressionOperation, a: Node, b: Node) -> Node {
+    match op {
+        ExpressionOperation::Addition => Node::Sum([terms(a), terms(b)].concat()),
+        ExpressionOperation::Subtraction => Node::Sum([terms(a), flip(terms(b))].concat()),
+        ExpressionOperation::Multiplication => Node::Product([factors(a), factors(b)].concat()),
+        ExpressionOperation::Division => Node::Product([factors(a), flip(factors(b))].concat()),
+        ExpressionOperation::Exponent => Node::Power(Box::new(a), Box::new(b)),
+    }
+}
+
+// the terms of a sum, with negative numbers and products with negative numbers written as
+// subtracted positive ones, so x + -2y is x - 2y
+fn terms(n: Node) -> Vec<(bool, Node)> {
+    match n {
+        Node::Sum(terms) => terms,
+        n => match sign(n) {
+            (true, n) => flip(terms(n)),
+            (false, n) => vec![(false, n)],
+        },
+    }
+}
+
+// whether a node is negative, and the node without its negative numbers. Factors of -1 are
+// left out, so -x is x negated.
+fn sign(n: Node) -> (bool, Node) {
+    match n {
+        Node::Leaf(ExpressionValue::Number(x)) if is_negative(&x) => (true, Node::Leaf(x.neg().into())),
+        Node::Product(factors) => {
+            let mut negative = false;
+            let mut positive = Vec::new();
+            for (divided, n) in factors {
+                match n {
+                    Node::Leaf(ExpressionValue::Number(x)) if is_negative(&x) => {
+                        negative = !negative;
+                        if x.numerator != 1 || x.denominator != 1 {
+                            positive.push((divided, Node::Leaf(x.neg().into())));
+                        }
+                    }
+                    n => positive.push((divided, n)),
+                }
+            }
+            // -1 -: y keeps its 1, since a product is written starting from its first factor
+            if positive.iter().all(|(divided, _)| *divided) {
+                positive.insert(0, (false, Node::Leaf(1.into())));
+            }
+            match positive.len() {
+                1 => (negative, positive.remove(0).1),
+                _ => (negative, Node::Product(positive)),
+            }
+        }
+        n => (false, n),
+    }
+}
+
+fn is_negative(x: &RationalNumber) -> bool {
+    x.negative && x.numerator != 0
+}
+
+fn factors(n: Node) -> Vec<(bool, Node)> {
+    match n {
+        Node::Product(factors) => factors,
+        n => vec![(false, n)],
+    }
+}
+
+fn flip(operands: Vec<(bool, Node)>) -> Vec<(bool, Node)> {
+    operands.into_iter().map(|(inverted, n)| (!inverted, n)).collect()
+}
+
+// compares nodes by how they are written, ignoring each number's display format
+fn key(n: &Node) -> String {
+    key_of(&write(n))
+}
+
+fn key_of(e: &Expression) -> String {
+    ExpressionFormatter::new()
+        .number_format(Some(NumberDisplayFormat::Fraction))
+        .format(e)
+}
+
+// each operand is written once, and its key and the sorting taken from what was written
+fn write(n: &Node) -> Expression {
+    match n {
+        Node::Leaf(val) => Expression::new(val.clone()),
+        Node::Power(base, exponent) => {
+            let base = match &**base {
+                Node::Leaf(val) if formatter::is_simple_base(val) => val.clone(),
+                base => write(base).into(),
+            };
+            let exponent = match &**exponent {
+                Node::Leaf(val) => val.clone(),
+                exponent => write(exponent).into(),
+            };
+            Expression::new(base).pow(exponent)
+        }
+        Node::Product(factors) => {
+            let mut factors: Vec<(bool, bool, String, &Node, Expression)> = factors
+                .iter()
+                .map(|(divided, n)| {
+                    let e = write(n);
+                    (*divided, !matches!(n, Node::Leaf(ExpressionValue::Number(_))), key_of(&e), n, e)
+                })
+                .collect();
+            factors.sort_by(|a, b| (a.0, a.1, &a.2).cmp(&(b.0, b.1, &b.2)));
+            let operands = factors.into_iter().map(|(divided, _, _, n, e)| {
+                let op = if divided {
+                    ExpressionOperation::Division
+                } else {
+                    ExpressionOperation::Multiplication
+                };
+                (op, n, e)
+            });
+            join(operands)
+        }
+        Node::Sum(terms) => {
+            let mut terms: Vec<(bool, bool, String, &Node, Expression)> = terms
+                .iter()
+                .map(|(subtracted, n)| {
+                    let e = write(n);
+                    (*subtracted, e.variables().is_empty(), key_of(&e), n, e)
+                })
+                .collect();
+            terms.sort_by(|a, b| (a.0, a.1, &a.2).cmp(&(b.0, b.1, &b.2)));
+            let leading_negative = terms.first().map(|t| t.0).unwrap_or(false);
+            let operands = terms.into_iter().map(|(subtracted, _, _, n, e)| {
+                let op = if subtracted {
+                    ExpressionOperation::Subtraction
+                } else {
+                    ExpressionOperation::Addition
+                };
+                (op, n, e)
+            });
+            let e = join(operands);
+            if leading_negative {
+                negate_first(e)
+            } else {
+                e
+            }
+        }
+    }
+}
+
+// writes each operand after its operation, grouping sums inside other nodes. The first
+// operation is left out.
+fn join<'a>(operands: impl Iterator<Item = (ExpressionOperation, &'a Node, Expression)>) -> Expression {
+    let mut values: Vec<ExpressionValue> = Vec::new();
+    let mut operations = Vec::new();
+    for (i, (op, n, e)) in operands.enumerate() {
+        if i > 0 {
+            operations.push(op);
+        }
+        match n {
+            Node::Sum(_) => values.push(e.into()),
+            _ => {
+                values.extend(e.values().iter().cloned());
+                operations.extend(e.operations().iter().cloned());
+            }
+        }
+    }
+    let mut values = values.into_iter();
+    let mut e = Expression::new(values.next().expect("failed to get first operand"));
+    for (op, val) in operations.into_iter().zip(values) {
+        e = e.push(op, val);
+    }
+    e
+}
+
+// negates the first term of a sum whose every term is subtracted
+fn negate_first(e: Expression) -> Expression {
+    let mut values = e.values().clone();
+    let mut operations = e.operations().clone();
+    match values[0] {
+        ExpressionValue::Number(n) if operations.first() != Some(&ExpressionOperation::Exponent) => {
+            values[0] = n.neg().into();
+        }
+        _ => {
+            values.insert(0, (-1).into());
+            operations.insert(0, ExpressionOperation::Multiplication);
+        }
+    }
+    let mut values = values.into_iter();
+    let mut negated = Expression::new(values.next().expect("failed to get first value"));
+    for (op, val) in operations.into_iter().zip(values) {
+        negated = negated.push(op, val);
+    }
+    negated
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::{Expression, ExpressionValue};
+    use crate::parse_expression;
+
+    fn canonical(s: &str) -> String {
+        parse_expression(s).unwrap().canonicalize().to_string()
+    }
+
+    fn equivalent(a: &str, b: &str) -> bool {
+        parse_expression(a).unwrap().is_equivalent(&parse_expression(b).unwrap())
+    }
+
+    #[test]
+    fn canonicalizes_expressions() {
+        assert_eq!(canonical("(b + a) + c"), "a + b + c");
+        assert_eq!(canonical("x * 3 * 2"), "2 * 3 * x");
+        assert_eq!(canonical("x - (y - z)"), "x + z - y");
+        assert_eq!(canonical("-3 + x"), "x - 3");
+        assert_eq!(canonical("-x - 3"), "-1 * x - 3");
+        assert_eq!(canonical("a -: (b -: c)"), "a * c -: b");
+        assert_eq!(canonical("2 * [(x + 1)^2 + ((4))]"), "2 * [(x + 1)^2 + 4]");
+        assert_eq!(canonical("(-3)^2 + 6/8"), "(-3)^2 + 3/4");
+        assert_eq!(canonical("max(y, 2x)"), "max(2 * x, y)");
+        assert_eq!(canonical("x + -2y"), "x - 2 * y");
+        assert_eq!(canonical("x + 3 * -(a + b)"), "x - 3 * (a + b)");
+
+        let nested = (0..40).fold(Expression::new(ExpressionValue::Variable("x".to_string())), |e, _| Expression::new(2) * (e + 1));
+        assert!(nested.canonicalize().to_string().contains("2 * [2 * (x + 1) + 1] + 1}"));
+
+        assert_eq!(canonical("x + -1 -: y"), "x - 1 -: y");
+        assert_eq!(canonical("x - -1 -: y"), "1 -: y + x");
+        for s in ["3 - (4 - 1) * 2 + 1/2 -: (2 * 3)", "2 + -1 -: 4", "2 - -1 -: 4", "2 + -3 * -1 -: 4"] {
+            let e = parse_expression(s).unwrap();
+            assert_eq!(e.canonicalize().evaluate(), e.evaluate(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn prints_minimal_parentheses() {
+        assert_eq!(parse_expression("(2 * 3) + (4 - 1)").unwrap().to_minimal_string(), "2 * 3 + 4 - 1");
+        assert_eq!(parse_expression("2 - (4 - 1)").unwrap().to_minimal_string(), "2 - (4 - 1)");
+    }
+
+    #[test]
+    fn finds_equivalent_expressions() {
+        assert!(equivalent("2x + 3", "3 + x * 2"));
+        assert!(equivalent("2x + 1/2", "0.5 + x * 2"));
+        assert!(equivalent("a - (b + c)", "a - c - b"));
+        assert!(equivalent("max(a, b)", "max(b, a)"));
+        assert!(equivalent("x - 2y", "x + -2y"));
+        assert!(equivalent("x - y", "x + -y"));
+        assert!(equivalent("a - 2 * 3 * b", "a + 2 * -3 * b"));
+        assert!(!equivalent("x - 2y", "x + 2y"));
+        assert!(!equivalent("x + -1 -: y", "x - y"));
+        assert!(equivalent("x + -1 -: y", "x - 1 -: y"));
+        assert!(!equivalent("x - y", "y - x"));
+        assert!(!equivalent("2(x + 1)", "2x + 2"));
+        assert!(!equivalent("2^3", "3^2"));
+    }
+}
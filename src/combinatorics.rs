@@ -0,0 +1,107 @@
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::{Error, Result};
+use std::convert::TryFrom;
+
+impl RationalNumber {
+    /// `n!` for a whole number `n`. Results too large for a `RationalNumber`, from `13!` on, are
+    /// `Error::Overflow`.
+    pub fn factorial(&self) -> Result<RationalNumber> {
+        let n = whole(self)?;
+        let product = (1..=n as u128).try_fold(1u128, |product, k| product.checked_mul(k)).ok_or(Error::Overflow)?;
+        number(product)
+    }
+
+    /// The number of ordered arrangements of `r` items chosen from `self`, `nPr = n!/(n - r)!`.
+    /// Zero when `r` is greater than `n`.
+    pub fn permutations(&self, r: &RationalNumber) -> Result<RationalNumber> {
+        let (n, r) = (whole(self)? as u128, whole(r)? as u128);
+        if r > n {
+            return number(0);
+        }
+        let product = (n - r + 1..=n).try_fold(1u128, |product, k| product.checked_mul(k)).ok_or(Error::Overflow)?;
+        number(product)
+    }
+
+    /// The number of ways to choose `r` items from `self` when order does not matter,
+    /// `nCr = n!/(r!(n - r)!)`. Zero when `r` is greater than `n`.
+    pub fn combinations(&self, r: &RationalNumber) -> Result<RationalNumber> {
+        let (n, r) = (whole(self)? as u128, whole(r)? as u128);
+        if r > n {
+            return number(0);
+        }
+        // C(n, k) = C(n, k - 1) * (n - k + 1) / k stays a whole number at every step
+        let r = r.min(n - r);
+        let mut c = 1u128;
+        for k in 1..=r {
+            c = c.checked_mul(n - r + k).ok_or(Error::Overflow)? / k;
+        }
+        number(c)
+    }
+}
+
+/// The probability of one of `favorable` equally likely outcomes out of `possible`, as a
+/// simplified fraction. Choosing both red marbles when drawing 2 of 3 red and 2 blue is
+/// `probability(C(3, 2), C(5, 2)) = 3/10`.
+pub fn probability(favorable: &RationalNumber, possible: &RationalNumber) -> Result<RationalNumber> {
+    let (favorable, possible) = (whole(favorable)?, whole(possible)?);
+    if possible == 0 {
+        return Err(Error::DenominatorCannotBeZero);
+    }
+    if favorable > possible {
+        return Err(Error::ConstraintsNotSatisfied);
+    }
+    Ok(RationalNumber::new(favorable, possible, false, NumberDisplayFormat::Fraction).simplify())
+}
+
+// the value of `n` if it is a whole number
+fn whole(n: &RationalNumber) -> Result<u32> {
+    if !n.numerator.is_multiple_of(n.denominator) || (n.negative && n.numerator != 0) {
+        return Err(Error::NotWholeNumber);
+    }
+    Ok(n.numerator / n.denominator)
+}
+
+fn number(n: u128) -> Result<RationalNumber> {
+    let n = u32::try_from(n).map_err(|_| Error::Overflow)?;
+    Ok(RationalNumber::new(n, 1, false, NumberDisplayFormat::Decimal(None)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::combinatorics::probability;
+    use crate::rational_number::RationalNumber;
+    use crate::Error;
+
+    fn n(n: i32) -> RationalNumber {
+        RationalNumber::from(n)
+    }
+
+    #[test]
+    fn counts_arrangements() {
+        assert_eq!(n(0).factorial().unwrap(), n(1));
+        assert_eq!(n(5).factorial().unwrap(), n(120));
+        assert_eq!(n(12).factorial().unwrap(), n(479001600));
+        assert!(matches!(n(13).factorial(), Err(Error::Overflow)));
+        assert!(matches!(n(-3).factorial(), Err(Error::NotWholeNumber)));
+        assert!(matches!(RationalNumber::parse("2.5").unwrap().factorial(), Err(Error::NotWholeNumber)));
+
+        assert_eq!(n(5).permutations(&n(2)).unwrap(), n(20));
+        assert_eq!(n(5).permutations(&n(0)).unwrap(), n(1));
+        assert_eq!(n(5).permutations(&n(6)).unwrap(), n(0));
+        assert_eq!(n(5).combinations(&n(2)).unwrap(), n(10));
+        assert_eq!(n(52).combinations(&n(5)).unwrap(), n(2598960));
+        assert_eq!(n(3).combinations(&n(4)).unwrap(), n(0));
+        assert!(matches!(n(5).combinations(&n(-1)), Err(Error::NotWholeNumber)));
+        assert!(matches!(n(40).permutations(&n(20)), Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn finds_probabilities() {
+        let favorable = n(3).combinations(&n(2)).unwrap();
+        let possible = n(5).combinations(&n(2)).unwrap();
+        assert_eq!(probability(&favorable, &possible).unwrap().to_string(), "3/10");
+        assert_eq!(probability(&n(1), &n(5).combinations(&n(2)).unwrap()).unwrap().to_string(), "1/10");
+        assert!(probability(&n(3), &n(0)).is_err());
+        assert!(probability(&n(6), &n(5)).is_err());
+    }
+}
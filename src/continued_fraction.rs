@@ -0,0 +1,199 @@
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::{Error, Result};
+use std::convert::TryFrom;
+use serde::{Serialize, Deserialize};
+use ts_rs::TS;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ContinuedFractionFormat {
+    Compact, // [3; 7, 16]
+    Nested,  // 3 + 1/(7 + 1/16)
+    Latex,   // 3 + \cfrac{1}{7 + \cfrac{1}{16}}
+}
+
+impl RationalNumber {
+    /// Expands the number into its finite simple continued fraction. The first term is the floor
+    /// of the number, so it is the only term that can be zero or negative.
+    pub fn continued_fraction(&self) -> Vec<i64> {
+        let mut n = self.numerator as i64;
+        if self.negative {
+            n = -n;
+        }
+        let mut d = self.denominator as i64;
+        let mut terms = Vec::new();
+        while d != 0 {
+            terms.push(n.div_euclid(d));
+            let r = n.rem_euclid(d);
+            n = d;
+            d = r;
+        }
+        terms
+    }
+
+    pub fn from_continued_fraction(terms: &[i64]) -> Result<Self> {
+        if terms.is_empty() || terms[1..].iter().any(|a| *a <= 0) {
+            return Err(Error::InvalidContinuedFraction);
+        }
+
+        let (mut h, mut k) = (1i128, 0i128);
+        let (mut h_prev, mut k_prev) = (0i128, 1i128);
+        for a in terms {
+            let a = *a as i128;
+            let next_h = a.checked_mul(h).and_then(|n| n.checked_add(h_prev)).ok_or(Error::Overflow)?;
+            let next_k = a.checked_mul(k).and_then(|n| n.checked_add(k_prev)).ok_or(Error::Overflow)?;
+            h_prev = h;
+            k_prev = k;
+            h = next_h;
+            k = next_k;
+        }
+
+        Ok(RationalNumber {
+            numerator: u32::try_from(h.abs()).map_err(|_| Error::Overflow)?,
+            denominator: u32::try_from(k).map_err(|_| Error::Overflow)?,
+            negative: h < 0,
+            format: if k == 1 {
+                NumberDisplayFormat::Decimal(None)
+            } else {
+                NumberDisplayFormat::Fraction
+            },
+        })
+    }
+
+    pub fn convergents(&self) -> Convergents {
+        Convergents {
+            terms: self.continued_fraction(),
+            index: 0,
+            h: (1, 0),
+            k: (0, 1),
+        }
+    }
+
+    /// Writes the expansion with each term written by `as_str`. Fails for a zero denominator,
+    /// which has no expansion.
+    pub fn as_continued_fraction_str(&self, format: ContinuedFractionFormat) -> Result<String> {
+        if self.denominator == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        }
+        let terms: Vec<String> = self.continued_fraction().into_iter().map(term_str).collect();
+        let (last, rest) = terms.split_last().expect("failed to get last term");
+        let s = match format {
+            ContinuedFractionFormat::Compact => match terms.split_first() {
+                Some((first, rest)) if !rest.is_empty() => format!("[{}; {}]", first, rest.join(", ")),
+                _ => format!("[{}]", last),
+            },
+            ContinuedFractionFormat::Nested => {
+                let mut s = last.clone();
+                for (i, a) in rest.iter().enumerate().rev() {
+                    s = if i == rest.len() - 1 {
+                        format!("{} + 1/{}", a, s)
+                    } else {
+                        format!("{} + 1/({})", a, s)
+                    };
+                }
+                s
+            }
+            ContinuedFractionFormat::Latex => {
+                let mut s = last.clone();
+                for a in rest.iter().rev() {
+                    s = format!("{} + \\cfrac{{1}}{{{}}}", a, s);
+                }
+                s
+            }
+        };
+        Ok(s)
+    }
+}
+
+// every term after the first is positive and none can exceed the original numerator
+fn term_str(a: i64) -> String {
+    RationalNumber {
+        numerator: a.unsigned_abs() as u32,
+        denominator: 1,
+        negative: a < 0,
+        format: NumberDisplayFormat::Decimal(None),
+    }
+    .as_str(None)
+}
+
+/// Iterates over the convergents of a continued fraction expansion, ending with the number itself.
+pub struct Convergents {
+    terms: Vec<i64>,
+    index: usize,
+    h: (i64, i64), // (h_{n-1}, h_{n-2})
+    k: (i64, i64), // (k_{n-1}, k_{n-2})
+}
+
+impl Iterator for Convergents {
+    type Item = RationalNumber;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let a = *self.terms.get(self.index)?;
+        self.index += 1;
+        let h = a * self.h.0 + self.h.1;
+        let k = a * self.k.0 + self.k.1;
+        self.h = (h, self.h.0);
+        self.k = (k, self.k.0);
+        Some(RationalNumber {
+            numerator: h.unsigned_abs() as u32,
+            denominator: k as u32,
+            negative: h < 0,
+            format: if k == 1 {
+                NumberDisplayFormat::Decimal(None)
+            } else {
+                NumberDisplayFormat::Fraction
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::continued_fraction::ContinuedFractionFormat;
+    use crate::rational_number::RationalNumber;
+    use crate::Error;
+
+    #[test]
+    fn expands_continued_fractions() {
+        assert_eq!(RationalNumber::parse("415/93").unwrap().continued_fraction(), vec![4, 2, 6, 7]);
+        assert_eq!(RationalNumber::parse("-7/3").unwrap().continued_fraction(), vec![-3, 1, 2]);
+        assert_eq!(RationalNumber::parse("5").unwrap().continued_fraction(), vec![5]);
+        assert_eq!(RationalNumber::parse("0").unwrap().continued_fraction(), vec![0]);
+
+        let n = RationalNumber::from_continued_fraction(&[4, 2, 6, 7]).unwrap();
+        assert_eq!(n.as_str(None), "415/93");
+        let n = RationalNumber::from_continued_fraction(&[-3, 1, 2]).unwrap();
+        assert_eq!(n.as_str(None), "-7/3");
+        assert!(RationalNumber::from_continued_fraction(&[]).is_err());
+        assert!(RationalNumber::from_continued_fraction(&[1, 0, 2]).is_err());
+    }
+
+    #[test]
+    fn lists_convergents() {
+        let convergents: Vec<String> = RationalNumber::parse("355/113")
+            .unwrap()
+            .convergents()
+            .map(|c| c.as_str(None))
+            .collect();
+        assert_eq!(convergents, vec!["3", "22/7", "355/113"]);
+    }
+
+    #[test]
+    fn prints_continued_fractions() {
+        let n = RationalNumber::parse("415/93").unwrap();
+        assert_eq!(n.as_continued_fraction_str(ContinuedFractionFormat::Compact).unwrap(), "[4; 2, 6, 7]");
+        assert_eq!(n.as_continued_fraction_str(ContinuedFractionFormat::Nested).unwrap(), "4 + 1/(2 + 1/(6 + 1/7))");
+        assert_eq!(
+            n.as_continued_fraction_str(ContinuedFractionFormat::Latex).unwrap(),
+            "4 + \\cfrac{1}{2 + \\cfrac{1}{6 + \\cfrac{1}{7}}}"
+        );
+        assert_eq!(RationalNumber::from(3).as_continued_fraction_str(ContinuedFractionFormat::Compact).unwrap(), "[3]");
+        let n = RationalNumber::parse("-7/3").unwrap();
+        assert_eq!(n.as_continued_fraction_str(ContinuedFractionFormat::Nested).unwrap(), "-3 + 1/(1 + 1/2)");
+        assert!(matches!(
+            RationalNumber::parse("5 1/0").unwrap().as_continued_fraction_str(ContinuedFractionFormat::Compact),
+            Err(Error::DenominatorCannotBeZero)
+        ));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::expression::{Expression, ExpressionOperation, ExpressionValue};
+use crate::formatter::OperatorSymbols;
+use crate::function::{Function, FunctionCall};
+use crate::svg::Svg;
+use crate::PlaceValue;
+use std::ptr;
+
+const NODE_RADIUS: f64 = 18.0;
+const NODE_SPACING: f64 = 48.0;
+const LEVEL_HEIGHT: f64 = 60.0;
+const HIGHLIGHT: &str = "#ffe08a";
+
+impl Expression {
+    /// The expression tree in Graphviz DOT. Operations and functions are ellipses with their
+    /// operands below them, and numbers, variables and radicals are boxes. Grouping symbols only
+    /// shape the tree and do not get nodes. With `highlight_next`, the operation or function that
+    /// `evaluate_next` applies next is filled in.
+    pub fn to_dot(&self, highlight_next: bool) -> String {
+        let tree = Tree::new(self, highlight_next);
+        let mut s = String::from("digraph expression {\n");
+        let mut id = 0;
+        write_dot(&tree, &mut id, &mut s);
+        s.push_str("}\n");
+        s
+    }
+
+    /// The same tree as `to_dot`, laid out and drawn as an SVG image.
+    pub fn to_svg(&self, highlight_next: bool) -> String {
+        let tree = Tree::new(self, highlight_next);
+        let leaves = tree.leaves() as f64;
+        let mut svg = Svg::new(leaves * NODE_SPACING, tree.height() as f64 * LEVEL_HEIGHT);
+        let mut next_leaf = 0.0;
+        let center = layout(&tree, 0, &mut next_leaf);
+        draw_edges(&center, &mut svg);
+        draw_nodes(&center, &mut svg);
+        svg.finish()
+    }
+}
+
+struct Tree {
+    label: String,
+    operation: bool, // drawn as an operation rather than a value
+    highlighted: bool,
+    children: Vec<Tree>,
+}
+
+// what `evaluate_next` reduces next: an operation at the top level of an expression, or a
+// function whose arguments are all reduced
+#[derive(Copy, Clone)]
+enum Next<'a> {
+    Operation(&'a Expression),
+    Function(&'a FunctionCall),
+}
+
+impl Tree {
+    fn new(e: &Expression, highlight_next: bool) -> Tree {
+        let next = if highlight_next { next(e) } else { None };
+        tree(e, next)
+    }
+
+    fn leaves(&self) -> usize {
+        if self.children.is_empty() {
+            1
+        } else {
+            self.children.iter().map(Tree::leaves).sum()
+        }
+    }
+
+    fn height(&self) -> usize {
+        1 + self.children.iter().map(Tree::height).max().unwrap_or(0)
+    }
+}
+
+// follows `evaluate_next`, which reduces the first group or function argument that can be
+// reduced before the operations around it
+fn next(e: &Expression) -> Option<Next<'_>> {
+    for val in e.values() {
+        match val {
+            ExpressionValue::Expression(group) => {
+                if let Some(next) = next(group) {
+                    return Some(next);
+                }
+            }
+            ExpressionValue::Function(call) => {
+                return call.arguments.iter().find_map(next).or(Some(Next::Function(call)));
+            }
+            _ => {}
+        }
+    }
+    if e.operations().is_empty() {
+        None
+    } else {
+        Some(Next::Operation(e))
+    }
+}
+
+fn tree(e: &Expression, next: Option<Next<'_>>) -> Tree {
+    // `fold` applies operations in the same order as `evaluate_next`, so the first one applied
+    // is the next one
+    let mut first = matches!(next, Some(Next::Operation(n)) if ptr::eq(n, e));
+    e.fold(
+        &mut |val, _| Ok(value(val, next)),
+        &ExpressionOperation::priority,
+        &mut |op, a, b| {
+            let highlighted = first;
+            first = false;
+            Ok(Tree {
+                label: OperatorSymbols::Unicode.symbol(op).trim().to_string(),
+                operation: true,
+                highlighted,
+                children: vec![a, b],
+            })
+        },
+    )
+    .expect("failed to build expression tree")
+}
+
+fn value(val: &ExpressionValue, next: Option<Next<'_>>) -> Tree {
+    let leaf = |label: String| Tree {
+        label,
+        operation: false,
+        highlighted: false,
+        children: Vec::new(),
+    };
+    match val {
+        ExpressionValue::Expression(group) => tree(group, next),
+        ExpressionValue::Number(n) => leaf(n.as_str(None)),
+        ExpressionValue::Variable(v) => leaf(v.clone()),
+        ExpressionValue::Radical(r) => leaf(r.to_string()),
+        ExpressionValue::Function(call) => Tree {
+            label: match call.function {
+                Function::Factorial => "!".to_string(),
+                Function::Round(PlaceValue::Ones) => "round".to_string(),
+                Function::Round(place_value) => format!("round to {}", place_value.as_str()),
+                function => function.name().to_string(),
+            },
+            operation: true,
+            highlighted: matches!(next, Some(Next::Function(n)) if ptr::eq(n, call)),
+            children: call.arguments.iter().map(|a| tree(a, next)).collect(),
+        },
+    }
+}
+
+// nodes are numbered in the order they are written, parents before children
+fn write_dot(tree: &Tree, id: &mut usize, s: &mut String) -> usize {
+    let node = *id;
+    *id += 1;
+    let shape = if tree.operation { "ellipse" } else { "box" };
+    let fill = if tree.highlighted {
+        format!(", style=filled, fillcolor=\"{}\"", HIGHLIGHT)
+    } else {
+        String::new()
+    };
+    s.push_str(&format!(
+        "    n{} [label=\"{}\", shape={}{}];\n",
+        node,
+        tree.label.replace('\\', "\\\\").replace('"', "\\\""),
+        shape,
+        fill
+    ));
+    for child in &tree.children {
+        let child = write_dot(child, id, s);
+        s.push_str(&format!("    n{} -> n{};\n", node, child));
+    }
+    node
+}
+
+// a tree node with its center in the drawing
+struct Placed<'a> {
+    tree: &'a Tree,
+    center: (f64, f64),
+    children: Vec<Placed<'a>>,
+}
+
+// leaves are spaced evenly from left to right and each parent is centered over its children
+fn layout<'a>(tree: &'a Tree, depth: usize, next_leaf: &mut f64) -> Placed<'a> {
+    let y = (depth as f64 + 0.5) * LEVEL_HEIGHT;
+    if tree.children.is_empty() {
+        let x = (*next_leaf + 0.5) * NODE_SPACING;
+        *next_leaf += 1.0;
+        return Placed {
+            tree,
+            center: (x, y),
+            children: Vec::new(),
+        };
+    }
+    let children: Vec<Placed> = tree.children.iter().map(|c| layout(c, depth + 1, next_leaf)).collect();
+    let x = children.iter().map(|c| c.center.0).sum::<f64>() / children.len() as f64;
+    Placed {
+        tree,
+        center: (x, y),
+        children,
+    }
+}
+
+fn draw_edges(node: &Placed, svg: &mut Svg) {
+    for child in &node.children {
+        svg.line(node.center, child.center, "black", 1.0);
+        draw_edges(child, svg);
+    }
+}
+
+fn draw_nodes(node: &Placed, svg: &mut Svg) {
+    let fill = if node.tree.highlighted { HIGHLIGHT } else { "white" };
+    if node.tree.operation {
+        svg.circle(node.center, NODE_RADIUS, fill, "black");
+    } else {
+        let (x, y) = node.center;
+        svg.rect((x - NODE_RADIUS, y - NODE_RADIUS), (2.0 * NODE_RADIUS, 2.0 * NODE_RADIUS), fill, "black");
+    }
+    svg.text(node.center, &node.tree.label);
+    for child in &node.children {
+        draw_nodes(child, svg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_expression;
+
+    fn highlighted(s: &str) -> Vec<String> {
+        parse_expression(s)
+            .unwrap()
+            .to_dot(true)
+            .lines()
+            .filter(|l| l.contains("filled"))
+            .map(|l| l.split('"').nth(1).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn writes_dot() {
+        let dot = parse_expression("2 + 3 * x").unwrap().to_dot(false);
+        assert_eq!(
+            dot,
+            "digraph expression {\n    n0 [label=\"+\", shape=ellipse];\n    n1 [label=\"2\", shape=box];\n    n0 -> n1;\n    n2 [label=\"×\", shape=ellipse];\n    n3 [label=\"3\", shape=box];\n    n2 -> n3;\n    n4 [label=\"x\", shape=box];\n    n2 -> n4;\n    n0 -> n2;\n}\n"
+        );
+        assert!(!dot.contains("filled"));
+    }
+
+    #[test]
+    fn highlights_next_step() {
+        assert_eq!(highlighted("2 + 3 * 4"), vec!["×"]);
+        assert_eq!(highlighted("2 * 3 + 4 * 5"), vec!["×"]);
+        assert_eq!(highlighted("(1 + 2) * (3 - 1)"), vec!["+"]);
+        assert_eq!(highlighted("(4) * [2 - (3 + 1)]"), vec!["+"]);
+        assert_eq!(highlighted("abs(-3) + 1"), vec!["abs"]);
+        assert_eq!(highlighted("round(2 * 1.25, tenths)"), vec!["×"]);
+        assert!(highlighted("7").is_empty());
+
+        let dot = parse_expression("(1 + 2) * 3").unwrap().to_dot(true);
+        assert_eq!(dot.matches("filled").count(), 1);
+        assert!(dot.contains("n1 [label=\"+\", shape=ellipse, style=filled"));
+    }
+
+    #[test]
+    fn draws_svg() {
+        let svg = parse_expression("2 + 3 * x").unwrap().to_svg(true);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"144\" height=\"180\""));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert_eq!(svg.matches("<rect").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 4);
+        assert_eq!(svg.matches("#ffe08a").count(), 1);
+        assert!(svg.contains(">×</text>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
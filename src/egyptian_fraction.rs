@@ -0,0 +1,167 @@
+use crate::expression::Expression;
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::{Error, Result};
+use std::convert::TryFrom;
+use serde::{Serialize, Deserialize};
+use ts_rs::TS;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EgyptianFractionMethod {
+    Greedy, // Fibonacci–Sylvester
+    Shortest { max_denominator: u32 },
+}
+
+impl RationalNumber {
+    /// Decomposes a proper fraction into a sum of distinct unit fractions, ordered from largest to
+    /// smallest.
+    pub fn egyptian_fractions(&self, method: EgyptianFractionMethod) -> Result<Vec<RationalNumber>> {
+        let n = self.simplify();
+        if n.negative || n.numerator == 0 || n.numerator >= n.denominator {
+            return Err(Error::NotProperFraction);
+        }
+
+        let denominators = match method {
+            EgyptianFractionMethod::Greedy => greedy_denominators(n.numerator as u128, n.denominator as u128)?,
+            EgyptianFractionMethod::Shortest { max_denominator } => {
+                shortest_denominators(n.numerator as u128, n.denominator as u128, max_denominator as u128)
+                    .ok_or(Error::EgyptianFractionNotFound)?
+            }
+        };
+
+        Ok(denominators
+            .into_iter()
+            .map(|d| RationalNumber::new(1, d, false, NumberDisplayFormat::Fraction))
+            .collect())
+    }
+
+    /// Same as `egyptian_fractions`, but joined with additions so the result can be displayed and
+    /// evaluated like any other expression.
+    pub fn egyptian_fraction_expression(&self, method: EgyptianFractionMethod) -> Result<Expression> {
+        let mut fractions = self.egyptian_fractions(method)?.into_iter();
+        let mut expr = Expression::new(fractions.next().expect("failed to get first unit fraction"));
+        for f in fractions {
+            expr = expr + f;
+        }
+        Ok(expr)
+    }
+}
+
+fn greedy_denominators(mut n: u128, mut d: u128) -> Result<Vec<u32>> {
+    let mut denominators = Vec::new();
+    while n != 0 {
+        let c = d.div_ceil(n);
+        denominators.push(u32::try_from(c).map_err(|_| Error::Overflow)?);
+
+        // n/d - 1/c
+        n = n.checked_mul(c).ok_or(Error::Overflow)? - d;
+        d = d.checked_mul(c).ok_or(Error::Overflow)?;
+        let f = crate::gcf_u128(n, d);
+        n /= f;
+        d /= f;
+    }
+    Ok(denominators)
+}
+
+fn shortest_denominators(n: u128, d: u128, max_denominator: u128) -> Option<Vec<u32>> {
+    let mut path = Vec::new();
+    for length in 1..=max_denominator {
+        // stop once even the smallest distinct unit fractions add up to too much
+        let smallest_sum: f64 = (max_denominator - length + 1..=max_denominator).map(|c| 1.0 / c as f64).sum();
+        if smallest_sum > n as f64 / d as f64 + f64::EPSILON {
+            break;
+        }
+        if search(n, d, length as usize, 2, max_denominator, &mut path) {
+            return Some(path.into_iter().map(|c| c as u32).collect());
+        }
+    }
+    None
+}
+
+// depth-first search for exactly `terms` unit fractions with increasing denominators
+fn search(n: u128, d: u128, terms: usize, min_c: u128, max_c: u128, path: &mut Vec<u128>) -> bool {
+    if terms == 1 {
+        if d.is_multiple_of(n) && d / n >= min_c && d / n <= max_c {
+            path.push(d / n);
+            return true;
+        }
+        return false;
+    }
+
+    let lower = min_c.max(d.div_ceil(n));
+    let upper = match (terms as u128).checked_mul(d) {
+        Some(bound) => max_c.min(bound / n),
+        None => max_c,
+    };
+    for c in lower..=upper {
+        // once the products overflow they do for every larger denominator too
+        let (nc, rest_d) = match (n.checked_mul(c), d.checked_mul(c)) {
+            (Some(nc), Some(dc)) => (nc, dc),
+            _ => break,
+        };
+        if nc <= d {
+            continue; // 1/c uses up the whole remainder
+        }
+        let rest_n = nc - d;
+        let f = crate::gcf_u128(rest_n, rest_d);
+        path.push(c);
+        if search(rest_n / f, rest_d / f, terms - 1, c + 1, max_c, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::egyptian_fraction::EgyptianFractionMethod;
+    use crate::rational_number::RationalNumber;
+
+    fn denominators(fractions: Vec<RationalNumber>) -> Vec<u32> {
+        fractions.iter().map(|f| f.denominator).collect()
+    }
+
+    #[test]
+    fn decomposes_greedily() {
+        let n = RationalNumber::parse("7/15").unwrap();
+        assert_eq!(denominators(n.egyptian_fractions(EgyptianFractionMethod::Greedy).unwrap()), vec![3, 8, 120]);
+
+        let n = RationalNumber::parse("6/8").unwrap();
+        assert_eq!(denominators(n.egyptian_fractions(EgyptianFractionMethod::Greedy).unwrap()), vec![2, 4]);
+
+        let n = RationalNumber::parse("5/121").unwrap();
+        assert!(n.egyptian_fractions(EgyptianFractionMethod::Greedy).is_err());
+
+        assert!(RationalNumber::parse("3/2").unwrap().egyptian_fractions(EgyptianFractionMethod::Greedy).is_err());
+        assert!(RationalNumber::parse("-1/2").unwrap().egyptian_fractions(EgyptianFractionMethod::Greedy).is_err());
+    }
+
+    #[test]
+    fn decomposes_shortest() {
+        let method = EgyptianFractionMethod::Shortest { max_denominator: 100 };
+        let n = RationalNumber::parse("7/15").unwrap();
+        assert_eq!(denominators(n.egyptian_fractions(method).unwrap()), vec![3, 9, 45]);
+
+        let method = EgyptianFractionMethod::Shortest { max_denominator: 1000 };
+        let n = RationalNumber::parse("5/121").unwrap();
+        assert_eq!(denominators(n.egyptian_fractions(method).unwrap()), vec![33, 121, 363]);
+
+        let method = EgyptianFractionMethod::Shortest { max_denominator: u32::MAX };
+        let n = RationalNumber::parse("4/5").unwrap();
+        assert_eq!(denominators(n.egyptian_fractions(method).unwrap()), vec![2, 4, 20]);
+
+        let method = EgyptianFractionMethod::Shortest { max_denominator: 5 };
+        let n = RationalNumber::parse("1/7").unwrap();
+        assert!(n.egyptian_fractions(method).is_err());
+    }
+
+    #[test]
+    fn builds_expressions() {
+        let n = RationalNumber::parse("7/15").unwrap();
+        let e = n.egyptian_fraction_expression(EgyptianFractionMethod::Greedy).unwrap();
+        assert_eq!(e.to_string(), "1/3 + 1/8 + 1/120");
+        assert_eq!(e.evaluate(), n);
+    }
+}
@@ -0,0 +1,404 @@
+use crate::expression::Expression;
+use crate::polynomial::{Polynomial, Term};
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::{Error, Result};
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use std::fmt;
+use ts_rs::TS;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Relation {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Relation {
+    pub fn as_str(&self) -> String {
+        let s = match self {
+            Relation::Equal => "=",
+            Relation::Less => "<",
+            Relation::LessOrEqual => "<=",
+            Relation::Greater => ">",
+            Relation::GreaterOrEqual => ">=",
+        };
+        s.to_string()
+    }
+
+    /// The relation after swapping sides or multiplying both sides by a negative number.
+    pub fn reverse(&self) -> Relation {
+        match self {
+            Relation::Equal => Relation::Equal,
+            Relation::Less => Relation::Greater,
+            Relation::LessOrEqual => Relation::GreaterOrEqual,
+            Relation::Greater => Relation::Less,
+            Relation::GreaterOrEqual => Relation::LessOrEqual,
+        }
+    }
+
+    pub fn holds(&self, a: &RationalNumber, b: &RationalNumber) -> bool {
+        match self {
+            Relation::Equal => a == b,
+            Relation::Less => a < b,
+            Relation::LessOrEqual => a <= b,
+            Relation::Greater => a > b,
+            Relation::GreaterOrEqual => a >= b,
+        }
+    }
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Equation {
+    pub left: Expression,
+    pub relation: Relation,
+    pub right: Expression,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Bound {
+    pub value: RationalNumber,
+    pub inclusive: bool,
+}
+
+/// An interval where a missing bound is unbounded in that direction.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Interval {
+    pub lower: Option<Bound>,
+    pub upper: Option<Bound>,
+}
+
+impl Interval {
+    pub fn contains(&self, n: &RationalNumber) -> bool {
+        let above = match &self.lower {
+            Some(b) if b.inclusive => *n >= b.value,
+            Some(b) => *n > b.value,
+            None => true,
+        };
+        let below = match &self.upper {
+            Some(b) if b.inclusive => *n <= b.value,
+            Some(b) => *n < b.value,
+            None => true,
+        };
+        above && below
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lower = match &self.lower {
+            Some(b) => format!("{}{}", if b.inclusive { "[" } else { "(" }, b.value),
+            None => "(-inf".to_string(),
+        };
+        let upper = match &self.upper {
+            Some(b) => format!("{}{}", b.value, if b.inclusive { "]" } else { ")" }),
+            None => "inf)".to_string(),
+        };
+        write!(f, "{}, {}", lower, upper)
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "value")]
+pub enum Solution {
+    Value(RationalNumber),
+    Interval(Interval),
+    NoSolution,
+    AllReals,
+}
+
+impl Solution {
+    pub fn contains(&self, n: &RationalNumber) -> bool {
+        match self {
+            Solution::Value(v) => v == n,
+            Solution::Interval(interval) => interval.contains(n),
+            Solution::NoSolution => false,
+            Solution::AllReals => true,
+        }
+    }
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Solution::Value(v) => write!(f, "{}", v),
+            Solution::Interval(interval) => write!(f, "{}", interval),
+            Solution::NoSolution => write!(f, "no solution"),
+            Solution::AllReals => write!(f, "all real numbers"),
+        }
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "value")]
+pub enum SolveAction {
+    Simplify, // combine like terms and order each side by degree
+    SwapSides,
+    Add(Term),
+    Subtract(Term),
+    Multiply(RationalNumber),
+    Divide(RationalNumber),
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquationStep {
+    pub action: SolveAction,
+    pub equation: Equation, // after the action
+}
+
+impl EquationStep {
+    pub fn as_str(&self) -> String {
+        let reverses = match &self.action {
+            SolveAction::Multiply(n) | SolveAction::Divide(n) => n.negative && self.equation.relation != Relation::Equal,
+            _ => false,
+        };
+        let s = match &self.action {
+            SolveAction::Simplify => "simplify each side".to_string(),
+            SolveAction::SwapSides => "swap the two sides".to_string(),
+            SolveAction::Add(t) => format!("add {} to both sides", Polynomial::from_term(t.clone())),
+            SolveAction::Subtract(t) => format!("subtract {} from both sides", Polynomial::from_term(t.clone())),
+            SolveAction::Multiply(n) => format!("multiply both sides by {}", n),
+            SolveAction::Divide(n) => format!("divide both sides by {}", n),
+        };
+        if reverses {
+            format!("{} and reverse the inequality", s)
+        } else {
+            s
+        }
+    }
+}
+
+impl Equation {
+    pub fn new(left: Expression, relation: Relation, right: Expression) -> Self {
+        Equation { left, relation, right }
+    }
+
+    pub fn variables(&self) -> Vec<char> {
+        let mut variables = self.left.variables();
+        variables.extend(self.right.variables());
+        variables.sort_unstable();
+        variables.dedup();
+        variables
+    }
+
+    /// Solves a linear equation or inequality in at most one variable, isolating the variable on
+    /// the left with inverse operations. Each step records the equation after that operation.
+    pub fn solve(&self) -> Result<(Solution, Vec<EquationStep>)> {
+        let variable = match self.variables().as_slice() {
+            [] => None,
+            [v] => Some(*v),
+            _ => return Err(Error::NotLinear),
+        };
+        let mut left = Polynomial::try_from(&self.left).map_err(|_| Error::NotLinear)?;
+        let mut right = Polynomial::try_from(&self.right).map_err(|_| Error::NotLinear)?;
+        if left.degree().unwrap_or(0) > 1 || right.degree().unwrap_or(0) > 1 {
+            return Err(Error::NotLinear);
+        }
+        let mut relation = self.relation;
+
+        let mut steps = Vec::new();
+        let mut push_step = |action, left: &Polynomial, relation, right: &Polynomial| {
+            steps.push(EquationStep {
+                action,
+                equation: Equation::new(left.to_expression(), relation, right.to_expression()),
+            });
+        };
+
+        let simplified = Equation::new(left.to_expression(), relation, right.to_expression());
+        if simplified.to_string() != self.to_string() {
+            push_step(SolveAction::Simplify, &left, relation, &right);
+        }
+
+        let coefficient = |p: &Polynomial| match variable {
+            Some(v) => p.terms().iter().find(|t| t.variables == [(v, 1)]).map(|t| t.coefficient),
+            None => None,
+        };
+        let constant = |p: &Polynomial| p.terms().iter().find(|t| t.variables.is_empty()).map(|t| t.coefficient);
+
+        if coefficient(&left).is_none() && coefficient(&right).is_some() {
+            std::mem::swap(&mut left, &mut right);
+            relation = relation.reverse();
+            push_step(SolveAction::SwapSides, &left, relation, &right);
+        }
+
+        if let (Some(v), Some(c)) = (variable, coefficient(&right)) {
+            let term = Term {
+                coefficient: c.abs(),
+                variables: vec![(v, 1)],
+            };
+            let p = Polynomial::from_term(term.clone());
+            let action = if c.negative {
+                left = left.checked_add(&p)?;
+                right = right.checked_add(&p)?;
+                SolveAction::Add(term)
+            } else {
+                left = left.checked_sub(&p)?;
+                right = right.checked_sub(&p)?;
+                SolveAction::Subtract(term)
+            };
+            push_step(action, &left, relation, &right);
+        }
+
+        if let (Some(_), Some(c)) = (coefficient(&left), constant(&left)) {
+            let term = Term {
+                coefficient: c.abs(),
+                variables: Vec::new(),
+            };
+            let p = Polynomial::from_term(term.clone());
+            let action = if c.negative {
+                left = left.checked_add(&p)?;
+                right = right.checked_add(&p)?;
+                SolveAction::Add(term)
+            } else {
+                left = left.checked_sub(&p)?;
+                right = right.checked_sub(&p)?;
+                SolveAction::Subtract(term)
+            };
+            push_step(action, &left, relation, &right);
+        }
+
+        let value = right.leading_coefficient();
+        let k = match coefficient(&left) {
+            Some(k) => k,
+            None => {
+                // the variable cancelled out, leaving a statement that is always true or false
+                let solution = if relation.holds(&left.leading_coefficient(), &value) {
+                    Solution::AllReals
+                } else {
+                    Solution::NoSolution
+                };
+                return Ok((solution, steps));
+            }
+        };
+
+        let one = RationalNumber::from(1);
+        let value = if k != one {
+            let action = if k.denominator == 1 {
+                SolveAction::Divide(k)
+            } else {
+                SolveAction::Multiply(k.reciprocal().simplify().set_display_format(NumberDisplayFormat::Fraction))
+            };
+            if k.negative {
+                relation = relation.reverse();
+            }
+            let value = value.checked_div(&k)?.simplify();
+            left = Polynomial::from_term(Term {
+                coefficient: one,
+                variables: left.terms()[0].variables.clone(),
+            });
+            right = Polynomial::constant(value);
+            push_step(action, &left, relation, &right);
+            value
+        } else {
+            value
+        };
+
+        let solution = match relation {
+            Relation::Equal => Solution::Value(value),
+            Relation::Less | Relation::LessOrEqual => Solution::Interval(Interval {
+                lower: None,
+                upper: Some(Bound {
+                    value,
+                    inclusive: relation == Relation::LessOrEqual,
+                }),
+            }),
+            Relation::Greater | Relation::GreaterOrEqual => Solution::Interval(Interval {
+                lower: Some(Bound {
+                    value,
+                    inclusive: relation == Relation::GreaterOrEqual,
+                }),
+                upper: None,
+            }),
+        };
+        Ok((solution, steps))
+    }
+}
+
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.left, self.relation, self.right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::equation::Solution;
+    use crate::parse_equation;
+    use crate::rational_number::RationalNumber;
+
+    fn solve(s: &str) -> (String, Vec<String>) {
+        let (solution, steps) = parse_equation(s).unwrap().solve().unwrap();
+        (
+            solution.to_string(),
+            steps.iter().map(|s| format!("{}: {}", s.as_str(), s.equation)).collect(),
+        )
+    }
+
+    #[test]
+    fn solves_equations() {
+        let (solution, steps) = solve("2x + 1 = 7");
+        assert_eq!(solution, "3");
+        assert_eq!(
+            steps,
+            vec!["subtract 1 from both sides: 2 * x = 6", "divide both sides by 2: x = 3"]
+        );
+
+        let (solution, steps) = solve("5 = 3 - x");
+        assert_eq!(solution, "-2");
+        assert_eq!(
+            steps,
+            vec![
+                "simplify each side: 5 = -x + 3",
+                "swap the two sides: -x + 3 = 5",
+                "subtract 3 from both sides: -x = 2",
+                "divide both sides by -1: x = -2",
+            ]
+        );
+
+        assert_eq!(solve("3(x - 1) = x + 5").0, "4");
+        assert_eq!(solve("2/3 x = 4").1.last().unwrap(), "multiply both sides by 3/2: x = 6");
+        assert_eq!(solve("x + 1 = x + 2").0, "no solution");
+        assert_eq!(solve("2(x + 1) = 2x + 2").0, "all real numbers");
+        assert!(parse_equation("x^2 = 4").unwrap().solve().is_err());
+        assert!(parse_equation("x + y = 4").unwrap().solve().is_err());
+    }
+
+    #[test]
+    fn solves_inequalities() {
+        let (solution, steps) = solve("-2x + 1 < 7");
+        assert_eq!(solution, "(-3, inf)");
+        assert_eq!(steps.last().unwrap(), "divide both sides by -2 and reverse the inequality: x > -3");
+
+        assert_eq!(solve("x - 4 <= 2").0, "(-inf, 6]");
+        assert_eq!(solve("3 ≥ x").0, "(-inf, 3]");
+        assert_eq!(solve("x > x").0, "no solution");
+
+        let (solution, _) = parse_equation("1/2 x >= 1").unwrap().solve().unwrap();
+        assert!(solution.contains(&RationalNumber::from(2)));
+        assert!(!solution.contains(&RationalNumber::from(1)));
+        assert_eq!(solution, parse_equation("2 <= x").unwrap().solve().unwrap().0);
+        assert_eq!(parse_equation("2 = 2").unwrap().solve().unwrap().0, Solution::AllReals);
+    }
+}
@@ -0,0 +1,436 @@
+use crate::expression::{variable_char, Expression, ExpressionOperation, ExpressionValue, GroupingSymbol};
+use crate::function::{Function, FunctionCall};
+use crate::radical::Radical;
+use crate::rational_number::RationalNumber;
+use crate::{Error, Result};
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops;
+use std::rc::Rc;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum UnaryOperation {
+    Negate,
+}
+
+/// An expression as a tree. Subtrees are reference counted, so cloning a tree or building a new
+/// one around an existing subtree does not copy it. Converting from an `Expression` keeps its
+/// grouping symbols as `Group` nodes, and converting back only adds groups where order of
+/// operations needs them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(RationalNumber),
+    Variable(char),
+    Radical(Radical),
+    Unary(UnaryOperation, Rc<Expr>),
+    Binary(ExpressionOperation, Rc<Expr>, Rc<Expr>),
+    Function(Function, Vec<Expr>),
+    Group(GroupingSymbol, Rc<Expr>),
+}
+
+/// Reduces an `Expr` from its leaves up, one method per kind of node. Groups pass their inner
+/// value through unless `group` is overridden.
+pub trait ExprFold {
+    type Output;
+
+    fn number(&mut self, n: &RationalNumber) -> Self::Output;
+    fn variable(&mut self, v: char) -> Self::Output;
+    fn radical(&mut self, r: &Radical) -> Self::Output;
+    fn unary(&mut self, op: UnaryOperation, operand: Self::Output) -> Self::Output;
+    fn binary(&mut self, op: &ExpressionOperation, left: Self::Output, right: Self::Output) -> Self::Output;
+    fn function(&mut self, function: Function, arguments: Vec<Self::Output>) -> Self::Output;
+
+    fn group(&mut self, _grouping: GroupingSymbol, inner: Self::Output) -> Self::Output {
+        inner
+    }
+}
+
+impl Expr {
+    pub fn number<T: Into<RationalNumber>>(n: T) -> Self {
+        Expr::Number(n.into())
+    }
+
+    pub fn variable(v: char) -> Self {
+        Expr::Variable(v)
+    }
+
+    pub fn binary(op: ExpressionOperation, left: Expr, right: Expr) -> Self {
+        Expr::Binary(op, Rc::new(left), Rc::new(right))
+    }
+
+    pub fn group(grouping: GroupingSymbol, inner: Expr) -> Self {
+        Expr::Group(grouping, Rc::new(inner))
+    }
+
+    pub fn neg(&self) -> Self {
+        Expr::Unary(UnaryOperation::Negate, Rc::new(self.clone()))
+    }
+
+    pub fn pow(&self, exponent: Expr) -> Self {
+        Expr::binary(ExpressionOperation::Exponent, self.clone(), exponent)
+    }
+
+    pub fn fold<F: ExprFold>(&self, f: &mut F) -> F::Output {
+        match self {
+            Expr::Number(n) => f.number(n),
+            Expr::Variable(v) => f.variable(*v),
+            Expr::Radical(r) => f.radical(r),
+            Expr::Unary(op, operand) => {
+                let operand = operand.fold(f);
+                f.unary(*op, operand)
+            }
+            Expr::Binary(op, left, right) => {
+                let left = left.fold(f);
+                let right = right.fold(f);
+                f.binary(op, left, right)
+            }
+            Expr::Function(function, arguments) => {
+                let arguments = arguments.iter().map(|a| a.fold(f)).collect();
+                f.function(*function, arguments)
+            }
+            Expr::Group(grouping, inner) => {
+                let inner = inner.fold(f);
+                f.group(*grouping, inner)
+            }
+        }
+    }
+
+    /// Evaluates the tree exactly. Variables, division by zero, irrational results and overflow are
+    /// errors.
+    pub fn evaluate(&self) -> Result<RationalNumber> {
+        self.fold(&mut Evaluator)
+    }
+
+    /// Variables used anywhere in the tree, in alphabetical order.
+    pub fn variables(&self) -> Vec<char> {
+        let mut variables = self.fold(&mut Variables);
+        variables.sort_unstable();
+        variables.dedup();
+        variables
+    }
+
+    /// Replaces every `variable` with `value`. Subtrees without the variable are shared with the
+    /// original tree rather than copied.
+    pub fn substitute(&self, variable: char, value: &Expr) -> Expr {
+        let replace = |child: &Rc<Expr>| {
+            if child.variables().contains(&variable) {
+                Rc::new(child.substitute(variable, value))
+            } else {
+                Rc::clone(child)
+            }
+        };
+        match self {
+            Expr::Variable(v) if *v == variable => value.clone(),
+            Expr::Unary(op, operand) => Expr::Unary(*op, replace(operand)),
+            Expr::Binary(op, left, right) => Expr::Binary(op.clone(), replace(left), replace(right)),
+            Expr::Function(function, arguments) => {
+                Expr::Function(*function, arguments.iter().map(|a| a.substitute(variable, value)).collect())
+            }
+            Expr::Group(grouping, inner) => Expr::Group(*grouping, replace(inner)),
+            e => e.clone(),
+        }
+    }
+
+    // the priority of the operation applied last, if there is one outside of any group
+    fn priority(&self) -> Option<u8> {
+        match self {
+            Expr::Binary(op, _, _) => Some(op.priority()),
+            Expr::Unary(UnaryOperation::Negate, _) => Some(ExpressionOperation::Multiplication.priority()),
+            _ => None,
+        }
+    }
+}
+
+struct Evaluator;
+
+impl ExprFold for Evaluator {
+    type Output = Result<RationalNumber>;
+
+    fn number(&mut self, n: &RationalNumber) -> Self::Output {
+        Ok(*n)
+    }
+
+    fn variable(&mut self, v: char) -> Self::Output {
+        Err(Error::UnboundVariable(v))
+    }
+
+    fn radical(&mut self, r: &Radical) -> Self::Output {
+        RationalNumber::try_from(*r)
+    }
+
+    fn unary(&mut self, op: UnaryOperation, operand: Self::Output) -> Self::Output {
+        match op {
+            UnaryOperation::Negate => Ok(operand?.neg()),
+        }
+    }
+
+    fn binary(&mut self, op: &ExpressionOperation, left: Self::Output, right: Self::Output) -> Self::Output {
+        op.checked_apply(left?, right?)
+    }
+
+    fn function(&mut self, function: Function, arguments: Vec<Self::Output>) -> Self::Output {
+        let arguments = arguments.into_iter().collect::<Result<Vec<RationalNumber>>>()?;
+        match function.apply(&arguments)? {
+            ExpressionValue::Number(n) => Ok(n),
+            ExpressionValue::Radical(r) => RationalNumber::try_from(r),
+            _ => Err(Error::NotRational),
+        }
+    }
+}
+
+struct Variables;
+
+impl ExprFold for Variables {
+    type Output = Vec<char>;
+
+    fn number(&mut self, _n: &RationalNumber) -> Self::Output {
+        Vec::new()
+    }
+
+    fn variable(&mut self, v: char) -> Self::Output {
+        vec![v]
+    }
+
+    fn radical(&mut self, _r: &Radical) -> Self::Output {
+        Vec::new()
+    }
+
+    fn unary(&mut self, _op: UnaryOperation, operand: Self::Output) -> Self::Output {
+        operand
+    }
+
+    fn binary(&mut self, _op: &ExpressionOperation, left: Self::Output, right: Self::Output) -> Self::Output {
+        [left, right].concat()
+    }
+
+    fn function(&mut self, _function: Function, arguments: Vec<Self::Output>) -> Self::Output {
+        arguments.concat()
+    }
+}
+
+impl From<&Expression> for Expr {
+    fn from(e: &Expression) -> Self {
+        e.fold(
+            &mut |val, _| {
+                Ok(match val {
+                    ExpressionValue::Number(n) => Expr::Number(*n),
+                    ExpressionValue::Variable(v) => Expr::Variable(variable_char(v)),
+                    ExpressionValue::Radical(r) => Expr::Radical(*r),
+                    ExpressionValue::Function(call) => {
+                        Expr::Function(call.function, call.arguments.iter().map(Expr::from).collect())
+                    }
+                    ExpressionValue::Expression(group) => Expr::group(group.grouping(), Expr::from(group)),
+                })
+            },
+            &ExpressionOperation::priority,
+            &mut |op, a, b| Ok(Expr::binary(op.clone(), a, b)),
+        )
+        .expect("failed to convert expression")
+    }
+}
+
+impl From<Expression> for Expr {
+    fn from(e: Expression) -> Self {
+        Expr::from(&e)
+    }
+}
+
+impl From<&Expr> for Expression {
+    fn from(e: &Expr) -> Self {
+        match e {
+            Expr::Number(n) => Expression::new(*n),
+            Expr::Variable(v) => Expression::new(ExpressionValue::Variable(v.to_string())),
+            Expr::Radical(r) => Expression::new(*r),
+            Expr::Function(function, arguments) => {
+                Expression::new(FunctionCall::new(*function, arguments.iter().map(Expression::from).collect()))
+            }
+            Expr::Group(grouping, inner) => Expression::new(Expression::from(&**inner).set_grouping(*grouping)),
+            Expr::Unary(UnaryOperation::Negate, operand) => match &**operand {
+                Expr::Number(n) => Expression::new(n.neg()),
+                operand => append(Expression::new(-1), ExpressionOperation::Multiplication, operand, true),
+            },
+            Expr::Binary(op, left, right) => {
+                // equal priority operations are applied left to right, so only a looser one on
+                // the left needs a group
+                let left = match left.priority() {
+                    Some(priority) if priority < op.priority() => Expression::new(Expression::from(&**left)),
+                    _ => Expression::from(&**left),
+                };
+                append(left, op.clone(), right, false)
+            }
+        }
+    }
+}
+
+impl From<Expr> for Expression {
+    fn from(e: Expr) -> Self {
+        Expression::from(&e)
+    }
+}
+
+// writes `right` after `left`, grouping it unless its last operation is tighter than `op`, or
+// as tight when `inline_equal` is set
+fn append(left: Expression, op: ExpressionOperation, right: &Expr, inline_equal: bool) -> Expression {
+    let right_expression = Expression::from(right);
+    let inline = match right.priority() {
+        Some(priority) => priority > op.priority() || (inline_equal && priority == op.priority()),
+        None => true,
+    };
+    if !inline {
+        return left.push(op, right_expression);
+    }
+    let mut e = left;
+    let mut values = right_expression.values().iter().cloned();
+    e = e.push(op, values.next().expect("failed to get first value"));
+    for (op, val) in right_expression.operations().iter().cloned().zip(values) {
+        e = e.push(op, val);
+    }
+    e
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Expression::from(self))
+    }
+}
+
+impl ops::Add for Expr {
+    type Output = Expr;
+
+    fn add(self, rhs: Expr) -> Self::Output {
+        Expr::binary(ExpressionOperation::Addition, self, rhs)
+    }
+}
+
+impl ops::Sub for Expr {
+    type Output = Expr;
+
+    fn sub(self, rhs: Expr) -> Self::Output {
+        Expr::binary(ExpressionOperation::Subtraction, self, rhs)
+    }
+}
+
+impl ops::Mul for Expr {
+    type Output = Expr;
+
+    fn mul(self, rhs: Expr) -> Self::Output {
+        Expr::binary(ExpressionOperation::Multiplication, self, rhs)
+    }
+}
+
+impl ops::Div for Expr {
+    type Output = Expr;
+
+    fn div(self, rhs: Expr) -> Self::Output {
+        Expr::binary(ExpressionOperation::Division, self, rhs)
+    }
+}
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Self::Output {
+        Expr::Unary(UnaryOperation::Negate, Rc::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::{Expr, ExprFold, UnaryOperation};
+    use crate::expression::{Expression, ExpressionOperation};
+    use crate::function::Function;
+    use crate::parse_expression;
+    use crate::radical::Radical;
+    use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+    use crate::Error;
+    use std::rc::Rc;
+
+    fn round_trip(s: &str) -> String {
+        Expression::from(Expr::from(parse_expression(s).unwrap())).to_string()
+    }
+
+    #[test]
+    fn converts_to_and_from_expressions() {
+        for s in [
+            "3 * (3 + 1) - [2 + 1/2]^2",
+            "2 - 4 - 1 + 6 -: 3 * 2",
+            "2^3^2 + x * y",
+            "|2 - x| + max(1, C(5, 2))",
+            "-1 * {4 - [2 + (1 - x)]}",
+        ] {
+            assert_eq!(round_trip(s), parse_expression(s).unwrap().to_string());
+        }
+
+        let e = Expr::from(parse_expression("2 + 3 * 4").unwrap());
+        match &e {
+            Expr::Binary(ExpressionOperation::Addition, _, right) => {
+                assert!(matches!(**right, Expr::Binary(ExpressionOperation::Multiplication, _, _)))
+            }
+            _ => panic!("expected a sum"),
+        }
+
+        let x = Expr::variable('x');
+        let e = (Expr::number(2) - (x.clone() - Expr::number(1))) * (x.clone() + Expr::number(3));
+        assert_eq!(e.to_string(), "[2 - (x - 1)] * (x + 3)");
+        assert_eq!((-(x.clone() + Expr::number(1))).to_string(), "-1 * (x + 1)");
+        assert_eq!(Expr::number(2).pow(Expr::number(3).pow(Expr::number(2))).to_string(), "2^(3^2)");
+    }
+
+    #[test]
+    fn evaluates_trees() {
+        let e = Expr::from(parse_expression("3 * (3 + 1) - [2 + 1/2]^2").unwrap());
+        assert_eq!(e.evaluate().unwrap().as_str(Some(NumberDisplayFormat::Mixed)), "5 3/4");
+        assert_eq!((-Expr::number(4)).evaluate().unwrap(), RationalNumber::from(-4));
+        assert!((Expr::number(1) / Expr::number(0)).evaluate().is_err());
+        assert!(Expr::variable('x').evaluate().is_err());
+
+        let power = |a: i32, b: i32| Expr::number(a).pow(Expr::number(b)).evaluate();
+        assert!(matches!(power(2, 100), Err(Error::Overflow)));
+        assert!(matches!(power(0, -1), Err(Error::DenominatorCannotBeZero)));
+        assert_eq!(power(2, -2).unwrap(), RationalNumber::new(1, 4, false, NumberDisplayFormat::Fraction));
+        assert!(matches!((Expr::number(100000) * Expr::number(100000)).evaluate(), Err(Error::Overflow)));
+        assert!(matches!(Expr::number(2).pow(Expr::number(1) / Expr::number(2)).evaluate(), Err(Error::NotRational)));
+        assert!(Expr::Function(Function::Sqrt, vec![Expr::number(2)]).evaluate().is_err());
+        assert_eq!(Expr::Radical(Radical::sqrt(RationalNumber::from(9)).unwrap()).evaluate().unwrap(), RationalNumber::from(3));
+    }
+
+    #[test]
+    fn folds_and_shares_subtrees() {
+        struct Depth;
+        impl ExprFold for Depth {
+            type Output = usize;
+            fn number(&mut self, _n: &RationalNumber) -> usize {
+                1
+            }
+            fn variable(&mut self, _v: char) -> usize {
+                1
+            }
+            fn radical(&mut self, _r: &Radical) -> usize {
+                1
+            }
+            fn unary(&mut self, _op: UnaryOperation, operand: usize) -> usize {
+                operand + 1
+            }
+            fn binary(&mut self, _op: &ExpressionOperation, left: usize, right: usize) -> usize {
+                left.max(right) + 1
+            }
+            fn function(&mut self, _function: Function, arguments: Vec<usize>) -> usize {
+                arguments.into_iter().max().unwrap_or(0) + 1
+            }
+        }
+
+        let e = Expr::from(parse_expression("(1 + 2) * x - 4").unwrap());
+        assert_eq!(e.fold(&mut Depth), 4);
+        assert_eq!(e.variables(), vec!['x']);
+
+        let substituted = e.substitute('x', &Expr::number(3));
+        assert_eq!(substituted.evaluate().unwrap(), RationalNumber::from(5));
+        let (Expr::Binary(_, product, _), Expr::Binary(_, substituted_product, _)) = (&e, &substituted) else {
+            panic!("expected a difference");
+        };
+        let (Expr::Binary(_, group, _), Expr::Binary(_, substituted_group, _)) = (&**product, &**substituted_product) else {
+            panic!("expected a product");
+        };
+        assert!(Rc::ptr_eq(group, substituted_group));
+    }
+}
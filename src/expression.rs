@@ -1,27 +1,128 @@
 use regex::Regex;
 
+use crate::formatter::ExpressionFormatter;
+use crate::function::{self, Function, FunctionCall};
+use crate::radical::Radical;
 use crate::rational_number::RationalNumber;
+use std::convert::TryFrom;
 use std::fmt;
 use std::ops;
 use crate::{Result, Error};
+use serde::{Serialize, Deserialize};
+use ts_rs::TS;
 
 const EXPONENT_RE: &str = r"^(?:\s*)\^(?:\s*)$";
-const DIVISION_RE: &str = r"^(?:\s*)(?:/|\-:)(?:\s*)$";
-const MULTIPLICATION_RE: &str = r"^(?:\s*)\*(?:\s*)$";
+const DIVISION_RE: &str = r"^(?:\s*)(?:/|\-:|÷)(?:\s*)$";
+const MULTIPLICATION_RE: &str = r"^(?:\s*)(?:\*|×|·)(?:\s*)$";
 const ADDITION_RE: &str = r"^(?:\s*)\+(?:\s*)$";
-const SUBTRACTION_RE: &str = r"^(?:\s*)\-(?:\s*)$";
+const SUBTRACTION_RE: &str = r"^(?:\s*)(?:\-|−)(?:\s*)$";
 
 pub(crate) fn parse_first_expression_value(s: &str) -> Result<(usize, ExpressionValue)> {
     if let Some((i, n)) = parse_first_number(s) {
         return Ok((i, n.into()));
-    } else if let Ok(expr) = parse_first_expression(s) {
-        if let Some((i, expr)) = expr {
-            return Ok((i, expr.into()));
-        }
+    } else if let Some((i, call)) = function::parse_first_function(s)? {
+        return Ok((i, call.into()));
+    } else if let Some((i, call)) = parse_first_root(s)? {
+        return Ok((i, call.into()));
+    } else if let Some((i, v)) = parse_first_variable(s) {
+        return Ok((i, v));
+    } else if let Some(i) = parse_negation(s) {
+        let (j, negation) = parse_negated_term(&s[i..])?;
+        return Ok((i + j, negation.into()));
+    } else if let Ok(Some((i, expr))) = parse_first_expression(s) {
+        return Ok((i, expr.into()));
     }
     Err(Error::ParseExpression)
 }
 
+pub(crate) fn parse_first_variable(expression: &str) -> Option<(usize, ExpressionValue)> {
+    let trimmed = expression.trim_start();
+    let c = trimmed.chars().next()?;
+    if crate::VARIABLES.contains(c) {
+        let i = expression.len() - trimmed.len() + c.len_utf8();
+        let rest = &expression[i..];
+        Some((i + rest.len() - rest.trim_start().len(), ExpressionValue::Variable(c.to_string())))
+    } else {
+        None
+    }
+}
+
+/// Parses a root symbol followed by digits, a variable or a group, such as `√50`, `√x` or
+/// `∛(2 * 4)`, into the same call as `sqrt(50)`. Returns the length parsed, including trailing
+/// whitespace.
+pub(crate) fn parse_first_root(expression: &str) -> Result<Option<(usize, FunctionCall)>> {
+    let trimmed = expression.trim_start();
+    let (index, rest) = if let Some(rest) = trimmed.strip_prefix('√') {
+        (2, rest)
+    } else if let Some(rest) = trimmed.strip_prefix('∛') {
+        (3, rest)
+    } else if let Some(rest) = trimmed.strip_prefix('∜') {
+        (4, rest)
+    } else {
+        return Ok(None);
+    };
+    let offset = expression.len() - rest.len();
+
+    let (i, radicand) = if let Some((i, group)) = parse_first_expression(rest)? {
+        (i, group)
+    } else if let Some((i, v)) = parse_first_variable(rest) {
+        (i, Expression::new(v))
+    } else {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.').len();
+        (digits, Expression::new(RationalNumber::parse(&rest[..digits])?))
+    };
+    let after = &rest[i..];
+    let whitespace = after.len() - after.trim_start().len();
+    Ok(Some((offset + i + whitespace, FunctionCall::new(Function::root(index), vec![radicand]))))
+}
+
+pub(crate) fn variable_char(v: &str) -> char {
+    v.chars().next().expect("failed to get variable")
+}
+
+fn parse_negation(expression: &str) -> Option<usize> {
+    let rest = expression.trim_start().strip_prefix(['-', '−'])?;
+    let negated = rest.trim_start().starts_with(['(', '[', '{', '|', '√', '∛', '∜'])
+        || parse_first_variable(rest).is_some()
+        || matches!(function::parse_first_function(rest), Ok(Some(_)));
+    if negated {
+        Some(expression.len() - rest.len())
+    } else {
+        None
+    }
+}
+
+// -x, -√2, -|x| and -(...) are read as a group multiplying -1 by the value and any powers or
+// factorial after it, so 6 -: -x is 6 -: (-1 * x) and -x^2 is -1 * x^2
+fn parse_negated_term(s: &str) -> Result<(usize, Expression)> {
+    let (mut i, val) = parse_first_expression_value(s)?;
+    let mut e = Expression::new(-1).push(ExpressionOperation::Multiplication, val);
+    loop {
+        if let Some((j, ExpressionOperation::Exponent)) = parse_first_operation(&s[i..]) {
+            let (k, val) = parse_first_expression_value(&s[i + j..])?;
+            i += j + k;
+            e = e.push(ExpressionOperation::Exponent, val);
+        } else if let Some(j) = function::parse_factorial(&s[i..]) {
+            i += j;
+            e = e.apply_postfix(Function::Factorial)?;
+        } else {
+            return Ok((i, e));
+        }
+    }
+}
+
+// a negated number is written the same as the negative number, so -(3) becomes -3 in one step
+fn negate_number(val: ExpressionValue) -> ExpressionValue {
+    if let ExpressionValue::Expression(e) = &val {
+        if let Some(term) = e.negated_term() {
+            if let [ExpressionValue::Number(n)] = term.values().as_slice() {
+                return n.neg().into();
+            }
+        }
+    }
+    val
+}
+
 pub(crate) fn parse_first_operation(expression: &str) -> Option<(usize, ExpressionOperation)> {
     let exponent_re = Regex::new(EXPONENT_RE).expect("invalid regex");
     let division_re = Regex::new(DIVISION_RE).expect("invalid regex");
@@ -31,7 +132,7 @@ pub(crate) fn parse_first_operation(expression: &str) -> Option<(usize, Expressi
 
     let mut n = None;
 
-    for i in 0..=expression.len() {
+    for i in (0..=expression.len()).filter(|i| expression.is_char_boundary(*i)) {
         let s = &expression[0..i];
         if exponent_re.is_match(s) {
             n = Some((i, ExpressionOperation::Exponent));
@@ -49,8 +150,13 @@ pub(crate) fn parse_first_operation(expression: &str) -> Option<(usize, Expressi
 }
 
 pub(crate) fn parse_first_number(expression: &str) -> Option<(usize, RationalNumber)> {
+    // a leading `−` (U+2212) is a minus sign
+    if let Some(rest) = expression.trim_start().strip_prefix('−') {
+        let (i, n) = parse_first_number(&format!("-{}", rest))?;
+        return Some((expression.len() - rest.len() + i - 1, n));
+    }
     let mut n = None;
-    for i in 0..=expression.len() {
+    for i in (0..=expression.len()).filter(|i| expression.is_char_boundary(*i)) {
         if let Ok(num) = RationalNumber::parse(&expression[0..i]) {
             n = Some((i, num))
         }
@@ -58,64 +164,48 @@ pub(crate) fn parse_first_number(expression: &str) -> Option<(usize, RationalNum
     n
 }
 
+/// Parses a group at the start of `expression` enclosed in `()`, `[]` or `{}`. Groups of any kind
+/// may nest inside each other, but each must be closed by its own symbol.
 pub fn parse_first_expression(expression: &str) -> Result<Option<(usize, Expression)>> {
-    let mut is_bracket = None;
-    let mut start = None;
-    let mut end = None;
-    let mut depth = 0;
-    for (i, c) in expression.chars().enumerate() {
-        if let Some(is_bracket) = is_bracket {
-            if is_bracket {
-                if c == '[' {
-                    depth += 1;
-                } else if c == ']' {
-                    depth -= 1;
-                    if depth == 0 {
-                        end = Some(i); // do not including grouping symbol at end
-                        break;
-                    }
-                }
-            } else {
-                if c == '(' {
-                    depth += 1;
-                } else if c == ')' {
-                    depth -= 1;
-                    if depth == 0 {
-                        end = Some(i); // do not including grouping symbol at end
-                        break;
-                    }
-                }
+    let trimmed = expression.trim_start();
+    let offset = expression.len() - trimmed.len();
+    let grouping = match trimmed.chars().next().and_then(GroupingSymbol::from_open) {
+        Some(grouping) => grouping,
+        None => return Ok(None),
+    };
+
+    let mut open = Vec::new();
+    for (i, c) in trimmed.char_indices() {
+        if let Some(g) = GroupingSymbol::from_open(c) {
+            open.push(g);
+        } else if let Some(g) = GroupingSymbol::from_close(c) {
+            if open.pop() != Some(g) {
+                return Err(Error::ParseExpression);
             }
-        } else {
-            if c == '(' {
-                is_bracket = Some(false);
-                start = Some(i + 1); // do not including grouping symbol at start
-                depth = 1;
-            } else if c == '[' {
-                is_bracket = Some(true);
-                start = Some(i + 1); // do not including grouping symbol at start
-                depth = 1;
+            if open.is_empty() {
+                // do not include the grouping symbols
+                let inner = &trimmed[grouping.open().len_utf8()..i];
+                if inner.trim().is_empty() {
+                    return Err(Error::ParseExpression);
+                }
+                let sub_expr = crate::parse_expression(inner)?.set_grouping(grouping);
+                return Ok(Some((offset + i + c.len_utf8(), sub_expr)));
             }
         }
     }
-
-    if let Some(start) = start {
-        let end = end.ok_or(Error::ParseExpression)?;
-        if start < end {
-            let sub_expr = crate::parse_expression(&expression[start..end])?;
-            Ok(Some((end + 1, sub_expr)))
-        } else {
-            Err(Error::ParseExpression)
-        }
-    } else {
-        Ok(None)
-    }
+    Err(Error::ParseExpression)
 }
 
-#[derive(Debug, Clone)]
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "value")]
 pub enum ExpressionValue {
     Expression(Expression),
     Number(RationalNumber),
+    Variable(String), // a single letter from `VARIABLES`
+    Radical(Radical),
+    Function(FunctionCall),
 }
 
 impl ExpressionValue {
@@ -132,6 +222,15 @@ impl ExpressionValue {
             _ => panic!("not a number"),
         }
     }
+
+    // a number, or a radical that simplifies to one
+    fn rational(&self) -> Option<RationalNumber> {
+        match self {
+            ExpressionValue::Number(n) => Some(*n),
+            ExpressionValue::Radical(r) => RationalNumber::try_from(*r).ok(),
+            _ => None,
+        }
+    }
 }
 
 impl From<Expression> for ExpressionValue {
@@ -140,6 +239,18 @@ impl From<Expression> for ExpressionValue {
     }
 }
 
+impl From<FunctionCall> for ExpressionValue {
+    fn from(call: FunctionCall) -> Self {
+        ExpressionValue::Function(call)
+    }
+}
+
+impl From<Radical> for ExpressionValue {
+    fn from(r: Radical) -> Self {
+        ExpressionValue::Radical(r)
+    }
+}
+
 impl From<RationalNumber> for ExpressionValue {
     fn from(n: RationalNumber) -> Self {
         ExpressionValue::Number(n)
@@ -164,10 +275,72 @@ impl From<f32> for ExpressionValue {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum GroupingSymbol {
+    #[default]
+    Parentheses,
+    Brackets,
+    Braces,
+}
+
+impl GroupingSymbol {
+    pub fn open(&self) -> char {
+        match self {
+            GroupingSymbol::Parentheses => '(',
+            GroupingSymbol::Brackets => '[',
+            GroupingSymbol::Braces => '{',
+        }
+    }
+
+    pub fn close(&self) -> char {
+        match self {
+            GroupingSymbol::Parentheses => ')',
+            GroupingSymbol::Brackets => ']',
+            GroupingSymbol::Braces => '}',
+        }
+    }
+
+    pub fn from_open(c: char) -> Option<Self> {
+        [GroupingSymbol::Parentheses, GroupingSymbol::Brackets, GroupingSymbol::Braces]
+            .iter()
+            .copied()
+            .find(|g| g.open() == c)
+    }
+
+    pub fn from_close(c: char) -> Option<Self> {
+        [GroupingSymbol::Parentheses, GroupingSymbol::Brackets, GroupingSymbol::Braces]
+            .iter()
+            .copied()
+            .find(|g| g.close() == c)
+    }
+
+    /// The symbol used one nesting level out: parentheses go inside brackets, which go inside
+    /// braces. Braces are used for every level beyond that.
+    pub fn enclosing(&self) -> Self {
+        match self {
+            GroupingSymbol::Parentheses => GroupingSymbol::Brackets,
+            GroupingSymbol::Brackets | GroupingSymbol::Braces => GroupingSymbol::Braces,
+        }
+    }
+
+    /// The symbol for a group with `depth` levels of groups nested inside it, so `0` is
+    /// parentheses.
+    pub fn for_depth(depth: usize) -> Self {
+        (0..depth).fold(GroupingSymbol::Parentheses, |g, _| g.enclosing())
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Expression {
     values: Vec<ExpressionValue>,
     operations: Vec<ExpressionOperation>,
+    #[serde(default)]
+    grouping: GroupingSymbol, // used when this expression is nested in another
 }
 
 impl Expression {
@@ -175,9 +348,58 @@ impl Expression {
         Expression {
             values: vec![n.into()],
             operations: Vec::new(),
+            grouping: GroupingSymbol::Parentheses,
         }
     }
 
+    pub fn grouping(&self) -> GroupingSymbol {
+        self.grouping
+    }
+
+    /// How many levels of groups are nested inside this expression, not counting groups inside
+    /// function arguments.
+    pub fn nesting_depth(&self) -> usize {
+        self.values
+            .iter()
+            .filter_map(|val| match val {
+                ExpressionValue::Expression(e) => Some(e.nesting_depth() + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The symbol to write this expression with when it is nested in another. It is the stored
+    /// `grouping` unless a group nested inside already uses it or a later symbol, in which case it
+    /// is moved out to the next one, so `((1 + 2) * 3)` is written `[(1 + 2) * 3]`.
+    pub fn display_grouping(&self) -> GroupingSymbol {
+        ExpressionFormatter::new().grouping_for(self)
+    }
+
+    /// The term a group negates, when it multiplies -1 by a single value raised to any powers,
+    /// as `-x` and `-(a + b)^2` are parsed.
+    pub(crate) fn negated_term(&self) -> Option<Expression> {
+        match (self.values.as_slice(), self.operations.as_slice()) {
+            ([ExpressionValue::Number(n), first, rest @ ..], [ExpressionOperation::Multiplication, operations @ ..])
+                if n.negative && n.numerator == 1 && n.denominator == 1
+                    && operations.iter().all(|op| *op == ExpressionOperation::Exponent) =>
+            {
+                let mut e = Expression::new(first.clone());
+                for (op, val) in operations.iter().zip(rest) {
+                    e = e.push(op.clone(), val.clone());
+                }
+                Some(e)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_grouping(&self, grouping: GroupingSymbol) -> Self {
+        let mut e = self.clone();
+        e.grouping = grouping;
+        e
+    }
+
     pub fn values(&self) -> &Vec<ExpressionValue> {
         &self.values
     }
@@ -186,6 +408,26 @@ impl Expression {
         &self.operations
     }
 
+    /// Applies a postfix operator such as `!` to the last value, so `2^3!` is `2^(3!)`. Repeating
+    /// it, as in `3!!`, is an error rather than a double factorial.
+    pub(crate) fn apply_postfix(&self, function: Function) -> Result<Self> {
+        let mut e = self.clone();
+        let last = e.values.pop().expect("failed to get last value");
+        let argument = match last {
+            ExpressionValue::Function(call) if call.function == function => return Err(Error::ParseExpression),
+            ExpressionValue::Expression(group) => group,
+            // the sign of a negative literal stays outside, so -3! is -(3!)
+            ExpressionValue::Number(n) if n.negative && n.numerator != 0 => {
+                let call = FunctionCall::new(function, vec![Expression::new(n.neg())]);
+                e.values.push(Expression::new(-1).push(ExpressionOperation::Multiplication, call).into());
+                return Ok(e);
+            }
+            val => Expression::new(val),
+        };
+        e.values.push(FunctionCall::new(function, vec![argument]).into());
+        Ok(e)
+    }
+
     pub fn pow<T: Into<ExpressionValue>>(&self, n: T) -> Self {
         let mut e = self.clone();
         e.values.push(n.into());
@@ -207,42 +449,49 @@ impl Expression {
                 match val {
                     ExpressionValue::Expression(e) => expr = e,
                     ExpressionValue::Number(n) => return n,
+                    ExpressionValue::Variable(v) => panic!("unbound variable {}", v),
+                    ExpressionValue::Radical(r) => return RationalNumber::try_from(r).expect("failed to get rational radical"),
+                    ExpressionValue::Function(call) => panic!("failed to apply {}", call),
                 }
             } else {
                 // if expression is only a number
                 return expr
                     .values
-                    .get(0)
+                    .first()
                     .expect("failed to get number")
-                    .number()
-                    .clone();
+                    .rational()
+                    .expect("failed to get rational number");
             }
         }
     }
 
+    /// Same as `evaluate`, but division by zero, overflow, irrational powers and variables are
+    /// errors instead of panics or approximations.
+    pub fn try_evaluate(&self) -> Result<RationalNumber> {
+        self.evaluate_with(&ExpressionOperation::priority, &mut |op, a, b| op.checked_apply(a, b))
+    }
+
     pub fn evaluate_next(&self) -> Option<ExpressionValue> {
         if let Some(e) = self.evaluate_next_expression() {
             Some(e.into())
-        } else if let Some(e) = self.evaluate_next_operation() {
-            Some(e)
         } else {
-            None
+            self.evaluate_next_operation()
         }
     }
 
     fn evaluate_next_expression(&self) -> Option<Expression> {
         let mut expr = None;
         for (i, val) in self.values.iter().enumerate() {
-            match val {
-                ExpressionValue::Expression(sub_expr) => {
-                    if let Some(sub_expr) = sub_expr.evaluate_next() {
-                        let mut e = self.clone();
-                        e.values[i] = sub_expr;
-                        expr = Some(e);
-                        break;
-                    }
-                }
-                _ => {}
+            let next = match val {
+                ExpressionValue::Expression(sub_expr) => sub_expr.evaluate_next().map(negate_number),
+                ExpressionValue::Function(call) => call.evaluate_next(),
+                _ => None,
+            };
+            if let Some(next) = next {
+                let mut e = self.clone();
+                e.values[i] = next;
+                expr = Some(e);
+                break;
             }
         }
         expr
@@ -252,7 +501,7 @@ impl Expression {
         if self.values.len() == 1 {
             return Some(
                 self.values
-                    .get(0)
+                    .first()
                     .expect("failed to get only value")
                     .clone(),
             );
@@ -272,15 +521,10 @@ impl Expression {
         if let Some((next_i, _next_priority)) = next_op {
             let mut e = self.clone();
             let op = e.operations.remove(next_i);
-            let a = *e.values.remove(next_i).number();
-            let b = *e.values.remove(next_i).number();
-            let val = match op {
-                ExpressionOperation::Exponent => a.pow(&b),
-                ExpressionOperation::Division => a / b,
-                ExpressionOperation::Multiplication => a * b,
-                ExpressionOperation::Addition => a + b,
-                ExpressionOperation::Subtraction => a - b,
-            };
+            // an irrational radical stays as it is
+            let a = e.values.remove(next_i).rational()?;
+            let b = e.values.remove(next_i).rational()?;
+            let val = op.apply(a, b);
             if e.values.is_empty() {
                 Some(val.into())
             } else {
@@ -291,34 +535,100 @@ impl Expression {
             None
         }
     }
-}
 
-impl fmt::Display for Expression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut s: String = "".to_string();
-        for (i, val) in self.values.iter().enumerate() {
-            let val = match val {
-                ExpressionValue::Expression(e) => format!("({})", e),
-                ExpressionValue::Number(n) => n.as_str(None),
-            };
+    /// Evaluates innermost groups first and then applies operations by `priority`, leftmost
+    /// first, the same order `evaluate_next` follows. Stops at the first error from `apply`, so
+    /// callers can both change the arithmetic and reject intermediate results.
+    pub fn evaluate_with<P, F>(&self, priority: &P, apply: &mut F) -> Result<RationalNumber>
+    where
+        P: Fn(&ExpressionOperation) -> OperationPriority,
+        F: FnMut(&ExpressionOperation, RationalNumber, RationalNumber) -> Result<RationalNumber>,
+    {
+        self.fold(
+            &mut |val, apply| match val {
+                ExpressionValue::Number(n) => Ok(*n),
+                ExpressionValue::Variable(v) => Err(Error::UnboundVariable(variable_char(v))),
+                ExpressionValue::Radical(r) => RationalNumber::try_from(*r),
+                ExpressionValue::Expression(e) => e.evaluate_with(priority, apply),
+                ExpressionValue::Function(call) => {
+                    let mut arguments = Vec::with_capacity(call.arguments.len());
+                    for arg in &call.arguments {
+                        arguments.push(arg.evaluate_with(priority, apply)?);
+                    }
+                    match call.function.apply(&arguments)? {
+                        ExpressionValue::Radical(r) => RationalNumber::try_from(r),
+                        val => val.rational().ok_or(Error::NotRational),
+                    }
+                }
+            },
+            priority,
+            apply,
+        )
+    }
 
-            let op = match self.operations.get(i) {
-                Some(op) => match op {
-                    ExpressionOperation::Exponent => Some("^"),
-                    ExpressionOperation::Division => Some(" -: "),
-                    ExpressionOperation::Multiplication => Some(" * "),
-                    ExpressionOperation::Addition => Some(" + "),
-                    ExpressionOperation::Subtraction => Some(" - "),
-                },
-                None => None,
-            };
+    /// Reduces the top level of the expression with operations ordered by `priority`, turning
+    /// each value into a `T` with `leaf` first.
+    pub(crate) fn fold<T, L, P, F>(&self, leaf: &mut L, priority: &P, apply: &mut F) -> Result<T>
+    where
+        L: FnMut(&ExpressionValue, &mut F) -> Result<T>,
+        P: Fn(&ExpressionOperation) -> OperationPriority,
+        F: FnMut(&ExpressionOperation, T, T) -> Result<T>,
+    {
+        let mut values = Vec::with_capacity(self.values.len());
+        for val in &self.values {
+            values.push(leaf(val, apply)?);
+        }
 
-            s.push_str(&val);
-            if let Some(op) = op {
-                s.push_str(op);
+        let mut operations = self.operations.clone();
+        while !operations.is_empty() {
+            let mut next_i = 0;
+            for (i, op) in operations.iter().enumerate() {
+                if priority(op) > priority(&operations[next_i]) {
+                    next_i = i;
+                }
             }
+            let op = operations.remove(next_i);
+            let a = values.remove(next_i);
+            let b = values.remove(next_i);
+            values.insert(next_i, apply(&op, a, b)?);
         }
-        write!(f, "{}", s)
+        values.pop().ok_or(Error::ParseExpression)
+    }
+
+    /// Variables used anywhere in the expression, in alphabetical order.
+    pub fn variables(&self) -> Vec<char> {
+        let mut variables = Vec::new();
+        for val in &self.values {
+            match val {
+                ExpressionValue::Variable(v) => variables.push(variable_char(v)),
+                ExpressionValue::Expression(e) => variables.extend(e.variables()),
+                ExpressionValue::Function(call) => variables.extend(call.variables()),
+                ExpressionValue::Number(_) | ExpressionValue::Radical(_) => {}
+            }
+        }
+        variables.sort_unstable();
+        variables.dedup();
+        variables
+    }
+
+    pub fn substitute<T: Into<ExpressionValue>>(&self, variable: char, value: T) -> Self {
+        let value = value.into();
+        let mut e = self.clone();
+        for val in e.values.iter_mut() {
+            match val {
+                ExpressionValue::Variable(v) if variable_char(v) == variable => *val = value.clone(),
+                ExpressionValue::Expression(sub_expr) => *sub_expr = sub_expr.substitute(variable, value.clone()),
+                ExpressionValue::Function(call) => *call = call.substitute(variable, &value),
+                _ => {}
+            }
+        }
+        e
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", ExpressionFormatter::new().format(self))
     }
 }
 
@@ -368,7 +678,9 @@ impl<T: Into<ExpressionValue>> ops::Sub<T> for Expression {
 
 pub type OperationPriority = u8;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ExpressionOperation {
     Exponent,
     Division,
@@ -390,7 +702,28 @@ impl fmt::Display for ExpressionOperation {
 }
 
 impl ExpressionOperation {
-    fn priority(&self) -> OperationPriority {
+    pub fn apply(&self, a: RationalNumber, b: RationalNumber) -> RationalNumber {
+        match self {
+            ExpressionOperation::Exponent => a.pow(&b),
+            ExpressionOperation::Division => a / b,
+            ExpressionOperation::Multiplication => a * b,
+            ExpressionOperation::Addition => a + b,
+            ExpressionOperation::Subtraction => a - b,
+        }
+    }
+
+    /// `apply` with checked arithmetic and exact powers.
+    pub fn checked_apply(&self, a: RationalNumber, b: RationalNumber) -> Result<RationalNumber> {
+        match self {
+            ExpressionOperation::Exponent => a.checked_pow(&b),
+            ExpressionOperation::Division => a.checked_div(&b),
+            ExpressionOperation::Multiplication => a.checked_mul(&b),
+            ExpressionOperation::Addition => a.checked_add(&b),
+            ExpressionOperation::Subtraction => a.checked_sub(&b),
+        }
+    }
+
+    pub(crate) fn priority(&self) -> OperationPriority {
         match self {
             ExpressionOperation::Exponent => 2,
             ExpressionOperation::Division => 1,
@@ -403,7 +736,7 @@ impl ExpressionOperation {
 
 #[cfg(test)]
 mod tests {
-    use crate::expression::Expression;
+    use crate::expression::{Expression, ExpressionOperation, ExpressionValue, GroupingSymbol};
     use crate::rational_number::NumberDisplayFormat;
 
     #[test]
@@ -433,6 +766,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn evaluates_with_custom_rules() {
+        let e = crate::parse_expression("2 + 3 * (4 - 1)").unwrap();
+        let left_to_right = e.evaluate_with(&|_| 0, &mut |op, a, b| Ok(op.apply(a, b)));
+        assert_eq!(left_to_right.unwrap().as_i32().unwrap(), 15);
+
+        let no_subtraction = e.evaluate_with(&|op| op.priority(), &mut |op, a, b| match op {
+            ExpressionOperation::Subtraction => Err(crate::Error::ParseExpression),
+            _ => Ok(op.apply(a, b)),
+        });
+        assert!(no_subtraction.is_err());
+    }
+
+    #[test]
+    fn serializes_round_trip() {
+        let e = crate::parse_expression("3(3 + 1) - [2 + 1/2]^2").unwrap();
+        let json = serde_json::to_string(&e).unwrap();
+        let e: Expression = serde_json::from_str(&json).unwrap();
+        assert_eq!(e.to_string(), "3 * (3 + 1) - [2 + 1/2]^2");
+        assert_eq!(e.evaluate().as_str(Some(NumberDisplayFormat::Mixed)), "5 3/4");
+
+        let json = serde_json::to_value(Expression::new(2) + 5).unwrap();
+        assert_eq!(json["values"][1]["type"], "Number");
+        assert_eq!(json["values"][1]["value"]["numerator"], 5);
+        assert_eq!(json["operations"][0], "Addition");
+    }
+
     #[test]
     fn evaluates_with_subexpressions() {
         let sub_a = Expression::new(4) + 1;
@@ -441,4 +801,60 @@ mod tests {
         let e = sub_a - sub_b;
         assert_eq!(e.evaluate().as_i32().unwrap(), -8);
     }
+
+    #[test]
+    fn parses_nested_grouping_symbols() {
+        let e = crate::parse_expression("2{3 + [4 - (1 + 1)]}").unwrap();
+        assert_eq!(e.to_string(), "2 * {3 + [4 - (1 + 1)]}");
+        assert_eq!(e.evaluate().as_i32().unwrap(), 10);
+        assert_eq!(e.nesting_depth(), 3);
+
+        let e = crate::parse_expression("((1 + 2) * 3) - (((4)))").unwrap();
+        assert_eq!(e.to_string(), "[(1 + 2) * 3] - {[(4)]}");
+        assert_eq!(crate::parse_expression("[2 + 1/2]^2").unwrap().to_string(), "[2 + 1/2]^2");
+        assert_eq!(crate::parse_expression("(1 + 2) * 3").unwrap().display_grouping(), GroupingSymbol::Brackets);
+        assert_eq!(Expression::new(1).set_grouping(GroupingSymbol::Braces).display_grouping(), GroupingSymbol::Braces);
+
+        assert!(crate::parse_expression("(1 + 2]").is_err());
+        assert!(crate::parse_expression("{1 + (2}").is_err());
+        assert!(crate::parse_expression("{}").is_err());
+    }
+
+    #[test]
+    fn parses_negations_as_a_unit() {
+        let evaluate = |s: &str| crate::parse_expression(s).unwrap().try_evaluate().unwrap().simplify().as_str(None);
+        assert_eq!(evaluate("6 -: -(1 + 2)"), "-2");
+        assert_eq!(evaluate("2^-(1 + 1)"), "0.25");
+        assert_eq!(evaluate("-(1 + 2)^2"), "-9");
+        assert_eq!(evaluate("12 -: -|3| * 2"), "-8");
+        assert_eq!(evaluate("8 -: -√4"), "-4");
+
+        let e = crate::parse_expression("6 -: -x").unwrap().substitute('x', 2);
+        assert_eq!(e.try_evaluate().unwrap().as_i32().unwrap(), -3);
+
+        assert_eq!(crate::parse_expression("2 * -x").unwrap().to_string(), "2 * -x");
+        assert_eq!(crate::parse_expression("-x^2 + -(1 + 2)").unwrap().to_string(), "-x^2 + -(1 + 2)");
+        assert_eq!(crate::parse_expression("(-x)^2").unwrap().to_string(), "(-x)^2");
+
+        let e = crate::parse_expression("6 -: -(1 + 2)").unwrap();
+        let steps: Vec<String> = std::iter::successors(Some(e), |e| match e.evaluate_next()? {
+            ExpressionValue::Expression(next) if next.values().len() > 1 => Some(next),
+            _ => None,
+        })
+        .map(|e| e.to_string())
+        .collect();
+        assert_eq!(steps, vec!["6 -: -(1 + 2)", "6 -: -3"]);
+    }
+
+    #[test]
+    fn parses_unicode_operators() {
+        let e = crate::parse_expression("6 × 2 ÷ 3 − 1").unwrap();
+        assert_eq!(e.to_string(), "6 * 2 -: 3 - 1");
+        assert_eq!(e.evaluate().as_i32().unwrap(), 3);
+
+        assert_eq!(crate::parse_expression("2·(−3)").unwrap().evaluate().as_i32().unwrap(), -6);
+        assert_eq!(crate::parse_expression("|−3| × −2").unwrap().evaluate().as_i32().unwrap(), -6);
+        assert_eq!(crate::parse_expression("−(4 ÷ 2)").unwrap().evaluate().as_i32().unwrap(), -2);
+        assert_eq!(crate::parse_expression("(√9 × √4) − 1").unwrap().evaluate().as_i32().unwrap(), 5);
+    }
 }
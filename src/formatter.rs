@@ -0,0 +1,359 @@
+use crate::expression::{Expression, ExpressionOperation, ExpressionValue, GroupingSymbol};
+use crate::function::Function;
+use crate::rational_number::NumberDisplayFormat;
+use serde::{Serialize, Deserialize};
+use ts_rs::TS;
+
+/// The symbols written for each operation.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum OperatorSymbols {
+    #[default]
+    Ascii,       // 6 -: 2 * 3 - 1
+    Unicode,     // 6 ÷ 2 × 3 − 1
+    Programming, // 6 / 2 * 3 - 1
+    Dot,         // 6 ÷ 2 · 3 − 1
+}
+
+impl OperatorSymbols {
+    pub fn symbol(&self, op: &ExpressionOperation) -> &'static str {
+        match (self, op) {
+            (_, ExpressionOperation::Exponent) => "^",
+            (_, ExpressionOperation::Addition) => " + ",
+            (OperatorSymbols::Ascii, ExpressionOperation::Division) => " -: ",
+            (OperatorSymbols::Programming, ExpressionOperation::Division) => " / ",
+            (_, ExpressionOperation::Division) => " ÷ ",
+            (OperatorSymbols::Unicode, ExpressionOperation::Multiplication) => " × ",
+            (OperatorSymbols::Dot, ExpressionOperation::Multiplication) => " · ",
+            (_, ExpressionOperation::Multiplication) => " * ",
+            (OperatorSymbols::Unicode, ExpressionOperation::Subtraction)
+            | (OperatorSymbols::Dot, ExpressionOperation::Subtraction) => " − ",
+            (_, ExpressionOperation::Subtraction) => " - ",
+        }
+    }
+
+    // the sign written before negative numbers
+    fn minus(&self) -> &'static str {
+        match self {
+            OperatorSymbols::Unicode | OperatorSymbols::Dot => "−",
+            OperatorSymbols::Ascii | OperatorSymbols::Programming => "-",
+        }
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum Parentheses {
+    #[default]
+    Explicit, // every nested expression is grouped, as it was written
+    Minimal,  // only groups that order of operations requires
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum GroupingStyle {
+    #[default]
+    AsWritten, // each group's own symbol, moved out a level when a group inside it needs to be
+    ByDepth,   // parentheses innermost, then brackets, then braces
+}
+
+/// Writes an `Expression` for a particular audience. The default matches `Display`, so
+/// `3(x + 1) -: 2` is written `3 * (x + 1) -: 2`.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpressionFormatter {
+    symbols: OperatorSymbols,
+    implicit_multiplication: bool,
+    parentheses: Parentheses,
+    grouping: GroupingStyle,
+    number_format: Option<NumberDisplayFormat>,
+}
+
+impl ExpressionFormatter {
+    pub fn new() -> Self {
+        ExpressionFormatter::default()
+    }
+
+    pub fn symbols(mut self, symbols: OperatorSymbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Leaves out the multiplication symbol before variables, groups, functions and radicals, as
+    /// in `3x`, `3(x + 1)` and `2√3`. It stays anywhere after a division in the same term, so
+    /// `6 -: 2 * x` is not written `6 -: 2x`, and after a power, so `y^2 * x` is not `y^2x`.
+    pub fn implicit_multiplication(mut self, implicit_multiplication: bool) -> Self {
+        self.implicit_multiplication = implicit_multiplication;
+        self
+    }
+
+    pub fn parentheses(mut self, parentheses: Parentheses) -> Self {
+        self.parentheses = parentheses;
+        self
+    }
+
+    pub fn grouping(mut self, grouping: GroupingStyle) -> Self {
+        self.grouping = grouping;
+        self
+    }
+
+    /// Writes every number in `format` instead of its own.
+    pub fn number_format(mut self, format: Option<NumberDisplayFormat>) -> Self {
+        self.number_format = format;
+        self
+    }
+
+    pub fn format(&self, e: &Expression) -> String {
+        self.write(e).0
+    }
+
+    // the expression and the outermost grouping symbol written inside it
+    fn write(&self, e: &Expression) -> (String, Option<GroupingSymbol>) {
+        let mut s = String::new();
+        let mut outermost: Option<GroupingSymbol> = None;
+        let mut previous: Option<&ExpressionValue> = None;
+        // where the base of the current chain of powers starts, and the outermost symbol in it
+        let mut base = (0, None);
+        // whether the current term has a division in it so far
+        let mut divided = false;
+        for (i, val) in e.values().iter().enumerate() {
+            let left = if i == 0 { None } else { e.operations().get(i - 1) };
+            let right = e.operations().get(i);
+            let (written, grouping) = self.write_value(val, left, right);
+            outermost = outermost.max(grouping);
+            match left {
+                Some(ExpressionOperation::Division) => divided = true,
+                Some(ExpressionOperation::Addition) | Some(ExpressionOperation::Subtraction) => divided = false,
+                _ => {}
+            }
+
+            if let (Some(op), Some(previous)) = (left, previous) {
+                let after_power = i >= 2 && e.operations()[i - 2] == ExpressionOperation::Exponent;
+                let implicit = self.implicit_multiplication
+                    && *op == ExpressionOperation::Multiplication
+                    && !divided
+                    && !after_power
+                    && implies_multiplication(previous, val, &written);
+                if !implicit {
+                    s.push_str(self.symbols.symbol(op));
+                }
+            }
+            if left != Some(&ExpressionOperation::Exponent) {
+                base = (s.len(), None);
+            }
+            s.push_str(&written);
+            base.1 = base.1.max(grouping);
+            previous = Some(val);
+
+            // powers are applied left to right, but a^b^c is commonly read as a^(b^c), so
+            // minimal parentheses write it (a^b)^c
+            let chained = left == Some(&ExpressionOperation::Exponent) && right == Some(&ExpressionOperation::Exponent);
+            if chained && self.parentheses == Parentheses::Minimal {
+                let grouping = match base.1 {
+                    Some(inner) => inner.enclosing(),
+                    None => GroupingSymbol::Parentheses,
+                };
+                s.insert(base.0, grouping.open());
+                s.push(grouping.close());
+                base.1 = Some(grouping);
+                outermost = outermost.max(Some(grouping));
+            }
+        }
+        (s, outermost)
+    }
+
+    // `left` and `right` are the operations on either side of the value
+    fn write_value(
+        &self,
+        val: &ExpressionValue,
+        left: Option<&ExpressionOperation>,
+        right: Option<&ExpressionOperation>,
+    ) -> (String, Option<GroupingSymbol>) {
+        match val {
+            ExpressionValue::Expression(group) => {
+                // (-x)^2 keeps its group, since -x^2 reads as -(x^2)
+                if group.negated_term().is_some() && right != Some(&ExpressionOperation::Exponent) {
+                    return self.write_inner(group);
+                }
+                if self.parentheses == Parentheses::Minimal {
+                    if let [only] = group.values().as_slice() {
+                        // a base or exponent that is not a single term keeps its group, so
+                        // 2^(1/2) is not written 2^1/2
+                        let power = left == Some(&ExpressionOperation::Exponent)
+                            || right == Some(&ExpressionOperation::Exponent);
+                        let unwrap = !power || is_simple_base(only) || matches!(only, ExpressionValue::Expression(_));
+                        if unwrap {
+                            return self.write_value(only, left, right);
+                        }
+                    } else if !needs_parentheses(group, left, right) {
+                        return self.write(group);
+                    }
+                }
+                let (inner, grouping) = self.enclose(group);
+                (format!("{}{}{}", grouping.open(), inner, grouping.close()), Some(grouping))
+            }
+            ExpressionValue::Number(n) => (self.signed(n.as_str(self.number_format)), None),
+            ExpressionValue::Variable(v) => (v.clone(), None),
+            ExpressionValue::Radical(r) => (self.signed(r.to_string()), None),
+            ExpressionValue::Function(call) => {
+                let arguments: Vec<String> = call.arguments.iter().map(|a| self.format(a)).collect();
+                (call.format_with(&arguments), None)
+            }
+        }
+    }
+
+    // a group's inner text and the symbol it is enclosed in
+    fn enclose(&self, group: &Expression) -> (String, GroupingSymbol) {
+        let (inner, outermost) = self.write_inner(group);
+        let grouping = match (self.grouping, outermost) {
+            (GroupingStyle::AsWritten, Some(outermost)) => outermost.enclosing().max(group.grouping()),
+            (GroupingStyle::AsWritten, None) => group.grouping(),
+            (GroupingStyle::ByDepth, Some(outermost)) => outermost.enclosing(),
+            (GroupingStyle::ByDepth, None) => GroupingSymbol::Parentheses,
+        };
+        (inner, grouping)
+    }
+
+    // the symbol a group is enclosed in when it is written nested in another expression
+    pub(crate) fn grouping_for(&self, group: &Expression) -> GroupingSymbol {
+        self.enclose(group).1
+    }
+
+    // a group without its grouping symbols, writing a negated term with a minus sign
+    fn write_inner(&self, group: &Expression) -> (String, Option<GroupingSymbol>) {
+        match group.negated_term() {
+            Some(term) => {
+                let (inner, outermost) = self.write(&term);
+                (format!("{}{}", self.symbols.minus(), inner), outermost)
+            }
+            None => self.write(group),
+        }
+    }
+
+    fn signed(&self, s: String) -> String {
+        match s.strip_prefix('-') {
+            Some(rest) => format!("{}{}", self.symbols.minus(), rest),
+            None => s,
+        }
+    }
+}
+
+// whether a group can be written without its grouping symbols between these operations, given
+// that equal priority operations are applied left to right
+fn needs_parentheses(group: &Expression, left: Option<&ExpressionOperation>, right: Option<&ExpressionOperation>) -> bool {
+    let loosest = match group.operations().iter().map(|op| op.priority()).min() {
+        Some(priority) => priority,
+        None => return false,
+    };
+    let after = match left {
+        Some(op) => {
+            loosest < op.priority()
+                || (loosest == op.priority()
+                    && matches!(
+                        op,
+                        ExpressionOperation::Subtraction | ExpressionOperation::Division | ExpressionOperation::Exponent
+                    ))
+        }
+        None => false,
+    };
+    // (a^b)^c keeps its group: powers are applied left to right, but a^b^c is commonly read as
+    // a^(b^c)
+    let before = match right {
+        Some(op) => loosest < op.priority() || *op == ExpressionOperation::Exponent,
+        None => false,
+    };
+    after || before
+}
+
+// a value that can be raised to a power without a group around it
+pub(crate) fn is_simple_base(val: &ExpressionValue) -> bool {
+    match val {
+        ExpressionValue::Number(n) => !n.negative && n.denominator == 1,
+        ExpressionValue::Variable(_) | ExpressionValue::Function(_) => true,
+        ExpressionValue::Expression(_) | ExpressionValue::Radical(_) => false,
+    }
+}
+
+// a multiplication symbol can be left out before a letter, a grouping symbol or a root, except
+// after a factorial, since `3!x` is easily misread
+fn implies_multiplication(left: &ExpressionValue, right: &ExpressionValue, right_written: &str) -> bool {
+    let starts_clearly = right_written
+        .chars()
+        .next()
+        .map(|c| c.is_alphabetic() || "([{|√∛∜".contains(c))
+        .unwrap_or(false);
+    let after_factorial = matches!(left, ExpressionValue::Function(call) if call.function == Function::Factorial);
+    starts_clearly && !after_factorial && !matches!(right, ExpressionValue::Number(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formatter::{ExpressionFormatter, GroupingStyle, OperatorSymbols, Parentheses};
+    use crate::parse_expression;
+    use crate::rational_number::NumberDisplayFormat;
+
+    fn format(formatter: ExpressionFormatter, s: &str) -> String {
+        formatter.format(&parse_expression(s).unwrap())
+    }
+
+    #[test]
+    fn formats_symbols() {
+        let s = "3(x + 1) -: 2 - -4";
+        assert_eq!(format(ExpressionFormatter::new(), s), parse_expression(s).unwrap().to_string());
+        assert_eq!(format(ExpressionFormatter::new().symbols(OperatorSymbols::Unicode), s), "3 × (x + 1) ÷ 2 − −4");
+        assert_eq!(format(ExpressionFormatter::new().symbols(OperatorSymbols::Programming), s), "3 * (x + 1) / 2 - -4");
+        assert_eq!(format(ExpressionFormatter::new().symbols(OperatorSymbols::Dot), s), "3 · (x + 1) ÷ 2 − −4");
+
+        let formatter = ExpressionFormatter::new().number_format(Some(NumberDisplayFormat::Fraction));
+        assert_eq!(format(formatter, "0.5 + 1 1/4"), "1/2 + 5/4");
+    }
+
+    #[test]
+    fn formats_implicit_multiplication() {
+        let formatter = ExpressionFormatter::new().implicit_multiplication(true);
+        assert_eq!(format(formatter, "3(x + 1)"), "3(x + 1)");
+        assert_eq!(format(formatter, "2 * x * y^2 - 3 * 4"), "2xy^2 - 3 * 4");
+        assert_eq!(format(formatter, "(x + 1)(x - 1)"), "(x + 1)(x - 1)");
+        assert_eq!(format(formatter, "6 -: 2x + 2√3"), "6 -: 2 * x + 2√3");
+        assert_eq!(format(formatter, "2 * 3! + 3!x"), "2 * 3! + 3! * x");
+        assert_eq!(format(formatter, "6 -: 2 * 3 * x + 2x"), "6 -: 2 * 3 * x + 2x");
+        assert_eq!(format(formatter, "y^2 * x"), "y^2 * x");
+    }
+
+    #[test]
+    fn formats_minimal_parentheses() {
+        let formatter = ExpressionFormatter::new().parentheses(Parentheses::Minimal);
+        assert_eq!(format(formatter, "(2 * 3) + (4 - 1)"), "2 * 3 + 4 - 1");
+        assert_eq!(format(formatter, "2 - (4 - 1) + (4 + 1)"), "2 - (4 - 1) + 4 + 1");
+        assert_eq!(format(formatter, "2 * (3 + 1) -: (2 * 2)"), "2 * (3 + 1) -: (2 * 2)");
+        assert_eq!(format(formatter, "((x))^2 + (-3)^2 + (2^3)^2"), "x^2 + (-3)^2 + (2^3)^2");
+        assert_eq!(format(formatter, "abs((2 + 1)) * [(1 + 1) - 3]"), "|2 + 1| * [1 + 1 - 3]");
+        assert_eq!(format(formatter, "2^(1/2) + 2^(1 1/2) + 2^(x)"), "2^(1/2) + 2^(1 1/2) + 2^x");
+        assert_eq!(format(formatter, "(2)^(3)^2"), "(2^3)^2");
+        assert_eq!(format(formatter, "2^3^2^x"), "[(2^3)^2]^x");
+        assert_eq!(format(formatter, "(1 + 1)^3^2"), "[(1 + 1)^3]^2");
+
+        for s in ["2 - (4 - 1) + (4 + 1)", "2 * (3 + 1) -: (2 * 2)", "((3))^2 - (2^3)^2", "[2 - (1 - 3)] -: (1/2)", "(2)^(3)^2"] {
+            let e = parse_expression(s).unwrap();
+            let minimal = parse_expression(&formatter.format(&e)).unwrap();
+            assert_eq!(minimal.evaluate(), e.evaluate());
+        }
+    }
+
+    #[test]
+    fn formats_grouping_by_depth() {
+        let s = "[(1 + 2) * 3] - {4 + 1}";
+        assert_eq!(format(ExpressionFormatter::new(), s), "[(1 + 2) * 3] - {4 + 1}");
+        let formatter = ExpressionFormatter::new().grouping(GroupingStyle::ByDepth);
+        assert_eq!(format(formatter, s), "[(1 + 2) * 3] - (4 + 1)");
+        assert_eq!(format(formatter, "2{3 + [4 - 1]}"), "2 * [3 + (4 - 1)]");
+
+        let formatter = formatter.parentheses(Parentheses::Minimal);
+        assert_eq!(format(formatter, "2{3 * [4 - (1 + 1)]}"), "2 * 3 * [4 - (1 + 1)]");
+    }
+}
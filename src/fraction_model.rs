@@ -0,0 +1,244 @@
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::svg::Svg;
+use crate::{Error, Result};
+
+const MAX_PARTS: u32 = 144; // per whole
+const MAX_WHOLES: u32 = 12;
+const MARGIN: f64 = 20.0;
+const GAP: f64 = 12.0;
+const SQUARE: f64 = 120.0;
+const BAR: (f64, f64) = (240.0, 36.0);
+const OBJECT_SPACING: f64 = 28.0;
+const LABEL_HEIGHT: f64 = 28.0;
+const SHADE: &str = "#8ecae6";
+const SECOND_SHADE: &str = "#ffb703";
+const OVERLAP_SHADE: &str = "#2a7f9e";
+
+impl RationalNumber {
+    /// Squares split into the denominator's parts, as close to a square grid as the denominator
+    /// allows, with one square per whole so `7/4` shades one whole and three fourths of another.
+    pub fn to_area_model_svg(&self) -> Result<String> {
+        let wholes = check(self)?;
+        let (rows, columns) = grid(self.denominator);
+        let (width, height) = (SQUARE / columns as f64, SQUARE / rows as f64);
+        let mut svg = Svg::new(2.0 * MARGIN + row_width(wholes, SQUARE), 2.0 * MARGIN + SQUARE);
+        for part in 0..wholes * self.denominator {
+            let (whole, i) = (part / self.denominator, part % self.denominator);
+            let x = MARGIN + whole as f64 * (SQUARE + GAP) + (i % columns) as f64 * width;
+            let y = MARGIN + (i / columns) as f64 * height;
+            svg.rect((x, y), (width, height), shade(part < self.numerator), "black");
+        }
+        Ok(svg.finish())
+    }
+
+    /// A bar for each whole, split into the denominator's parts.
+    pub fn to_bar_model_svg(&self) -> Result<String> {
+        let wholes = check(self)?;
+        let mut svg = Svg::new(2.0 * MARGIN + row_width(wholes, BAR.0), 2.0 * MARGIN + BAR.1);
+        bars(&mut svg, MARGIN, self.denominator, &[(self.numerator, SHADE)]);
+        Ok(svg.finish())
+    }
+
+    /// A set of as many circles as the denominator for each whole, with the numerator's circles
+    /// shaded.
+    pub fn to_set_model_svg(&self) -> Result<String> {
+        let wholes = check(self)?;
+        let (rows, columns) = grid(self.denominator);
+        let size = (columns as f64 * OBJECT_SPACING, rows as f64 * OBJECT_SPACING);
+        let mut svg = Svg::new(2.0 * MARGIN + row_width(wholes, size.0 + GAP), 2.0 * MARGIN + size.1 + GAP);
+        for whole in 0..wholes {
+            let x = MARGIN + whole as f64 * (size.0 + 2.0 * GAP);
+            svg.rect((x, MARGIN), (size.0 + GAP, size.1 + GAP), "none", "black");
+            for i in 0..self.denominator {
+                let center = (
+                    x + GAP / 2.0 + ((i % columns) as f64 + 0.5) * OBJECT_SPACING,
+                    MARGIN + GAP / 2.0 + ((i / columns) as f64 + 0.5) * OBJECT_SPACING,
+                );
+                let shaded = whole * self.denominator + i < self.numerator;
+                svg.circle(center, OBJECT_SPACING / 2.0 - 3.0, shade(shaded), "black");
+            }
+        }
+        Ok(svg.finish())
+    }
+
+    /// Bar models of both addends rewritten over their least common denominator, above a bar of
+    /// the sum with each addend's parts in its own color.
+    pub fn to_addition_model_svg(&self, other: &RationalNumber) -> Result<String> {
+        check(self)?;
+        check(other)?;
+        let denominator = crate::lcm(self.denominator, other.denominator);
+        let a = equivalent(self, denominator);
+        let b = equivalent(other, denominator);
+        let sum = RationalNumber::new(a.numerator + b.numerator, denominator, false, NumberDisplayFormat::Fraction);
+        let wholes = check(&sum)?;
+
+        let mut label = format!("{} + {} = {} + {} = {}", fraction(self), fraction(other), fraction(&a), fraction(&b), fraction(&sum));
+        let simplest = sum.simplify().as_str(Some(NumberDisplayFormat::Mixed));
+        if simplest != fraction(&sum) {
+            label = format!("{} = {}", label, simplest);
+        }
+
+        let width = 2.0 * MARGIN + row_width(wholes, BAR.0);
+        let mut svg = Svg::new(width, 2.0 * MARGIN + LABEL_HEIGHT + 3.0 * BAR.1 + 2.0 * GAP);
+        svg.text((width / 2.0, MARGIN + LABEL_HEIGHT / 2.0), &label);
+        let top = MARGIN + LABEL_HEIGHT;
+        bars(&mut svg, top, denominator, &[(a.numerator, SHADE)]);
+        bars(&mut svg, top + BAR.1 + GAP, denominator, &[(b.numerator, SECOND_SHADE)]);
+        bars(
+            &mut svg,
+            top + 2.0 * (BAR.1 + GAP),
+            denominator,
+            &[(a.numerator, SHADE), (b.numerator, SECOND_SHADE)],
+        );
+        Ok(svg.finish())
+    }
+
+    /// A square with this fraction shaded in columns and `other` in rows, so the overlap is the
+    /// product. Both fractions must be at most one.
+    pub fn to_multiplication_model_svg(&self, other: &RationalNumber) -> Result<String> {
+        check(self)?;
+        check(other)?;
+        if self.numerator > self.denominator || other.numerator > other.denominator {
+            return Err(Error::NotProperFraction);
+        }
+        let product = (*self * *other).set_display_format(NumberDisplayFormat::Fraction);
+        let mut label = format!("{} × {} = {}", fraction(self), fraction(other), fraction(&product));
+        let simplest = product.simplify();
+        if simplest.denominator != product.denominator {
+            label = format!("{} = {}", label, fraction(&simplest));
+        }
+
+        let side = 2.0 * SQUARE;
+        let (width, height) = (side / self.denominator as f64, side / other.denominator as f64);
+        let mut svg = Svg::new(2.0 * MARGIN + side, 2.0 * MARGIN + LABEL_HEIGHT + side);
+        svg.text((MARGIN + side / 2.0, MARGIN + LABEL_HEIGHT / 2.0), &label);
+        for row in 0..other.denominator {
+            for column in 0..self.denominator {
+                let fill = match (column < self.numerator, row < other.numerator) {
+                    (true, true) => OVERLAP_SHADE,
+                    (true, false) => SHADE,
+                    (false, true) => SECOND_SHADE,
+                    (false, false) => "white",
+                };
+                let corner = (MARGIN + column as f64 * width, MARGIN + LABEL_HEIGHT + row as f64 * height);
+                svg.rect(corner, (width, height), fill, "black");
+            }
+        }
+        Ok(svg.finish())
+    }
+}
+
+// the number of wholes a model needs, at least one so zero is an empty whole
+fn check(n: &RationalNumber) -> Result<u32> {
+    if n.denominator == 0 {
+        return Err(Error::DenominatorCannotBeZero);
+    }
+    if n.negative && n.numerator != 0 {
+        return Err(Error::NotPositive);
+    }
+    let wholes = n.numerator.div_ceil(n.denominator).max(1);
+    if n.denominator > MAX_PARTS || wholes > MAX_WHOLES {
+        return Err(Error::Overflow);
+    }
+    Ok(wholes)
+}
+
+// the most square rows and columns for a number of parts, with no more rows than columns
+fn grid(parts: u32) -> (u32, u32) {
+    let rows = (1..=parts).take_while(|r| r * r <= parts).filter(|r| parts.is_multiple_of(*r)).last().unwrap_or(1);
+    (rows, parts / rows)
+}
+
+fn row_width(wholes: u32, size: f64) -> f64 {
+    wholes as f64 * (size + GAP) - GAP
+}
+
+// a row of bars split into `denominator` parts, shaded by each run of parts in order
+fn bars(svg: &mut Svg, y: f64, denominator: u32, runs: &[(u32, &str)]) {
+    let total: u32 = runs.iter().map(|(parts, _)| parts).sum();
+    let wholes = total.div_ceil(denominator).max(1);
+    let mut fills = runs.iter().flat_map(|(parts, fill)| (0..*parts).map(move |_| *fill));
+    let width = BAR.0 / denominator as f64;
+    for part in 0..wholes * denominator {
+        let (whole, i) = (part / denominator, part % denominator);
+        let x = MARGIN + whole as f64 * (BAR.0 + GAP) + i as f64 * width;
+        svg.rect((x, y), (width, BAR.1), fills.next().unwrap_or("white"), "black");
+    }
+}
+
+fn shade(shaded: bool) -> &'static str {
+    if shaded {
+        SHADE
+    } else {
+        "white"
+    }
+}
+
+fn equivalent(n: &RationalNumber, denominator: u32) -> RationalNumber {
+    let factor = denominator / n.denominator;
+    RationalNumber::new(n.numerator * factor, denominator, false, NumberDisplayFormat::Fraction)
+}
+
+fn fraction(n: &RationalNumber) -> String {
+    n.as_str(Some(NumberDisplayFormat::Fraction))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rational_number::RationalNumber;
+    use crate::Error;
+
+    fn n(s: &str) -> RationalNumber {
+        RationalNumber::parse(s).unwrap()
+    }
+
+    fn shaded(svg: &str, color: &str) -> usize {
+        svg.matches(&format!("fill=\"{}\"", color)).count()
+    }
+
+    #[test]
+    fn draws_single_models() {
+        let area = n("3/4").to_area_model_svg().unwrap();
+        assert_eq!(area.matches("<rect").count(), 4);
+        assert_eq!(shaded(&area, "#8ecae6"), 3);
+        assert!(area.contains("<rect x=\"80\" y=\"80\" width=\"60\" height=\"60\" fill=\"white\""));
+
+        let bar = n("1 2/3").to_bar_model_svg().unwrap();
+        assert!(bar.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"532\" height=\"76\""));
+        assert_eq!(bar.matches("<rect").count(), 6);
+        assert_eq!(shaded(&bar, "#8ecae6"), 5);
+
+        let set = n("7/6").to_set_model_svg().unwrap();
+        assert_eq!(set.matches("<circle").count(), 12);
+        assert_eq!(shaded(&set, "#8ecae6"), 7);
+
+        let zero = n("0/5").to_bar_model_svg().unwrap();
+        assert_eq!(zero.matches("<rect").count(), 5);
+        assert_eq!(shaded(&zero, "#8ecae6"), 0);
+
+        assert!(matches!(n("-1/2").to_area_model_svg(), Err(Error::NotPositive)));
+        assert!(matches!(n("40/3").to_bar_model_svg(), Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn draws_operation_models() {
+        let sum = n("3/4").to_addition_model_svg(&n("1/6")).unwrap();
+        assert!(sum.contains(">3/4 + 1/6 = 9/12 + 2/12 = 11/12</text>"));
+        assert_eq!(sum.matches("<rect").count(), 36);
+        assert_eq!(shaded(&sum, "#8ecae6"), 18);
+        assert_eq!(shaded(&sum, "#ffb703"), 4);
+
+        let sum = n("3/4").to_addition_model_svg(&n("1/2")).unwrap();
+        assert!(sum.contains(">3/4 + 1/2 = 3/4 + 2/4 = 5/4 = 1 1/4</text>"));
+        assert_eq!(sum.matches("<rect").count(), 16);
+
+        let product = n("1/2").to_multiplication_model_svg(&n("2/3")).unwrap();
+        assert!(product.contains(">1/2 × 2/3 = 2/6 = 1/3</text>"));
+        assert_eq!(product.matches("<rect").count(), 6);
+        assert_eq!(shaded(&product, "#2a7f9e"), 2);
+        assert_eq!(shaded(&product, "#8ecae6"), 1);
+        assert_eq!(shaded(&product, "#ffb703"), 2);
+
+        assert!(matches!(n("3/2").to_multiplication_model_svg(&n("1/2")), Err(Error::NotProperFraction)));
+    }
+}
@@ -0,0 +1,445 @@
+use crate::expression::{Expression, ExpressionValue};
+use crate::radical::Radical;
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::{Error, PlaceValue, Result};
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use std::fmt;
+use ts_rs::TS;
+
+const NAMES: [&str; 10] = ["abs", "sqrt", "root", "min", "max", "round", "floor", "ceil", "C", "P"];
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "type", content = "value")]
+pub enum Function {
+    Abs,
+    Sqrt,
+    /// The root of the given index, `∛x` or `∜x`. Square roots are `Sqrt`.
+    Root(u32),
+    Min,
+    Max,
+    Round(PlaceValue),
+    Floor,
+    Ceil,
+    Factorial,
+    Combinations,
+    Permutations,
+}
+
+impl Function {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Abs => "abs",
+            Function::Sqrt => "sqrt",
+            Function::Root(_) => "root",
+            Function::Min => "min",
+            Function::Max => "max",
+            Function::Round(_) => "round",
+            Function::Floor => "floor",
+            Function::Ceil => "ceil",
+            Function::Factorial => "factorial",
+            Function::Combinations => "C",
+            Function::Permutations => "P",
+        }
+    }
+
+    /// The root of the given index, `Sqrt` for index 2.
+    pub fn root(index: u32) -> Self {
+        match index {
+            2 => Function::Sqrt,
+            index => Function::Root(index),
+        }
+    }
+
+    /// Applies the function exactly. Roots of numbers that are not perfect powers give a
+    /// `Radical`, every other result is a number.
+    pub fn apply(&self, arguments: &[RationalNumber]) -> Result<ExpressionValue> {
+        let n = *arguments.first().ok_or(Error::ParseExpression)?;
+        let result = match self {
+            Function::Abs => n.abs(),
+            Function::Sqrt => return root(n, 2),
+            Function::Root(index) => return root(n, *index),
+            Function::Min => arguments.iter().fold(n, |min, a| if *a < min { *a } else { min }),
+            Function::Max => arguments.iter().fold(n, |max, a| if *a > max { *a } else { max }),
+            Function::Round(place_value) => round(n, *place_value)?,
+            Function::Floor => whole(n, false)?,
+            Function::Ceil => whole(n, true)?,
+            Function::Factorial => n.factorial()?,
+            Function::Combinations => n.combinations(arguments.get(1).ok_or(Error::ParseExpression)?)?,
+            Function::Permutations => n.permutations(arguments.get(1).ok_or(Error::ParseExpression)?)?,
+        };
+        Ok(result.into())
+    }
+}
+
+/// A function applied to its arguments, written `abs(x)` or `|x|`, `sqrt(x)` or `√x`, `min(a, b)`
+/// or `round(x, tenths)`.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionCall {
+    pub function: Function,
+    pub arguments: Vec<Expression>,
+}
+
+impl FunctionCall {
+    pub fn new(function: Function, arguments: Vec<Expression>) -> Self {
+        FunctionCall { function, arguments }
+    }
+
+    /// Takes one step in the leftmost argument that is not yet a number, or applies the function
+    /// once they all are. `None` if the function cannot be applied, such as `sqrt(-4)`.
+    pub fn evaluate_next(&self) -> Option<ExpressionValue> {
+        for (i, arg) in self.arguments.iter().enumerate() {
+            if number(arg).is_none() {
+                let mut call = self.clone();
+                call.arguments[i] = match arg.evaluate_next()? {
+                    ExpressionValue::Expression(e) => e,
+                    val => Expression::new(val),
+                };
+                return Some(call.into());
+            }
+        }
+        let arguments: Option<Vec<RationalNumber>> = self.arguments.iter().map(number).collect();
+        self.function.apply(&arguments?).ok()
+    }
+
+    pub fn variables(&self) -> Vec<char> {
+        self.arguments.iter().flat_map(|a| a.variables()).collect()
+    }
+
+    pub fn substitute(&self, variable: char, value: &ExpressionValue) -> Self {
+        let mut call = self.clone();
+        for arg in call.arguments.iter_mut() {
+            *arg = arg.substitute(variable, value.clone());
+        }
+        call
+    }
+
+    // writes the call around its arguments, already formatted
+    pub(crate) fn format_with(&self, arguments: &[String]) -> String {
+        let arguments = arguments.join(", ");
+        match self.function {
+            Function::Abs => format!("|{}|", arguments),
+            Function::Factorial => match self.arguments[0].values().as_slice() {
+                [ExpressionValue::Number(n)] if !n.negative => format!("{}!", arguments),
+                [ExpressionValue::Variable(_)] => format!("{}!", arguments),
+                _ => format!("({})!", arguments),
+            },
+            Function::Sqrt => format!("√{}", radicand(arguments)),
+            Function::Root(3) => format!("∛{}", radicand(arguments)),
+            Function::Root(4) => format!("∜{}", radicand(arguments)),
+            Function::Root(index) => format!("root({}, {})", arguments, index),
+            Function::Round(PlaceValue::Ones) => format!("round({})", arguments),
+            Function::Round(place_value) => format!("round({}, {})", arguments, place_value.as_str()),
+            function => format!("{}({})", function.name(), arguments),
+        }
+    }
+}
+
+impl fmt::Display for FunctionCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arguments: Vec<String> = self.arguments.iter().map(|a| a.to_string()).collect();
+        write!(f, "{}", self.format_with(&arguments))
+    }
+}
+
+// a radicand of digits or a single variable is written right after the root symbol, anything
+// else in parentheses
+fn radicand(argument: String) -> String {
+    let digits = argument.chars().all(|c| c.is_ascii_digit() || c == '.');
+    let variable = argument.chars().count() == 1 && crate::VARIABLES.contains(argument.as_str());
+    if digits || variable {
+        argument
+    } else {
+        format!("({})", argument)
+    }
+}
+
+fn root(n: RationalNumber, index: u32) -> Result<ExpressionValue> {
+    let r = Radical::root(n, index)?;
+    Ok(match RationalNumber::try_from(r) {
+        Ok(n) => n.into(),
+        Err(_) => r.into(),
+    })
+}
+
+// the value of an argument that is already a single number
+fn number(arg: &Expression) -> Option<RationalNumber> {
+    match arg.values().as_slice() {
+        [ExpressionValue::Number(n)] => Some(*n),
+        [ExpressionValue::Radical(r)] => RationalNumber::try_from(*r).ok(),
+        _ => None,
+    }
+}
+
+/// Parses a function call at the start of `expression`, either a name followed by its
+/// parenthesized arguments or absolute value bars. Returns the length parsed, including trailing
+/// whitespace.
+pub(crate) fn parse_first_function(expression: &str) -> Result<Option<(usize, FunctionCall)>> {
+    let trimmed = expression.trim_start();
+    let offset = expression.len() - trimmed.len();
+
+    let (len, call) = if let Some(rest) = trimmed.strip_prefix('|') {
+        let end = closing_bar(rest).ok_or(Error::ParseExpression)?;
+        let argument = crate::parse_expression(&rest[..end])?;
+        (1 + end + 1, FunctionCall::new(Function::Abs, vec![argument]))
+    } else {
+        let name = match NAMES.iter().find(|name| trimmed.starts_with(**name)) {
+            Some(name) => *name,
+            None => return Ok(None),
+        };
+        let after_name = &trimmed[name.len()..];
+        let rest = match after_name.trim_start().strip_prefix('(') {
+            Some(rest) => rest,
+            None => return Ok(None),
+        };
+        let end = closing_parenthesis(rest).ok_or(Error::ParseExpression)?;
+        let arguments = split_arguments(&rest[..end]);
+        let call = parse_call(name, &arguments)?;
+        (trimmed.len() - rest.len() + end + 1, call)
+    };
+
+    let after = &trimmed[len..];
+    Ok(Some((offset + len + after.len() - after.trim_start().len(), call)))
+}
+
+/// Parses a postfix `!` at the start of `expression`, returning the length parsed, including
+/// trailing whitespace.
+pub(crate) fn parse_factorial(expression: &str) -> Option<usize> {
+    let rest = expression.trim_start().strip_prefix('!')?;
+    Some(expression.len() - rest.trim_start().len())
+}
+
+fn parse_call(name: &str, arguments: &[&str]) -> Result<FunctionCall> {
+    let parse = |args: &[&str]| args.iter().map(|a| crate::parse_expression(a)).collect::<Result<Vec<Expression>>>();
+    let (function, arguments) = match (name, arguments) {
+        ("abs", [_]) => (Function::Abs, parse(arguments)?),
+        ("sqrt", [_]) => (Function::Sqrt, parse(arguments)?),
+        ("floor", [_]) => (Function::Floor, parse(arguments)?),
+        ("ceil", [_]) => (Function::Ceil, parse(arguments)?),
+        ("min", [_, _, ..]) => (Function::Min, parse(arguments)?),
+        ("max", [_, _, ..]) => (Function::Max, parse(arguments)?),
+        ("round", [_]) => (Function::Round(PlaceValue::Ones), parse(arguments)?),
+        ("C", [_, _]) => (Function::Combinations, parse(arguments)?),
+        ("P", [_, _]) => (Function::Permutations, parse(arguments)?),
+        ("round", [x, place_value]) => (Function::Round(parse_place_value(place_value)?), parse(&[x])?),
+        ("root", [x, index]) => (Function::root(parse_index(index)?), parse(&[x])?),
+        _ => return Err(Error::ParseExpression),
+    };
+    Ok(FunctionCall::new(function, arguments))
+}
+
+fn parse_index(s: &str) -> Result<u32> {
+    match s.trim().parse() {
+        Ok(index) if index >= 2 => Ok(index),
+        _ => Err(Error::ParseExpression),
+    }
+}
+
+fn parse_place_value(s: &str) -> Result<PlaceValue> {
+    let s = s.trim().to_lowercase();
+    (-6..=6)
+        .map(PlaceValue::from)
+        .find(|p| p.as_str() == s)
+        .ok_or(Error::ParseExpression)
+}
+
+// byte index of the `)` closing a group whose `(` comes just before `s`
+fn closing_parenthesis(s: &str) -> Option<usize> {
+    let mut depth = 1;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// byte index of the `|` closing an absolute value whose `|` comes just before `s`. A bar opens
+// another absolute value when it follows an operator, an opening symbol or another opening bar.
+fn closing_bar(s: &str) -> Option<usize> {
+    let mut depth = 1;
+    let mut after_opening = true;
+    for (i, c) in s.char_indices() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c == '|' {
+            if after_opening {
+                depth += 1;
+            } else {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+        } else {
+            after_opening = "+-−*×·/÷^:([{,".contains(c);
+        }
+    }
+    None
+}
+
+// splits on commas outside of any group or absolute value
+fn split_arguments(s: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(&s[start..]);
+    arguments
+}
+
+// rounds half away from zero, so 2.25 to tenths is 2.3 and -2.25 is -2.3
+fn round(n: RationalNumber, place_value: PlaceValue) -> Result<RationalNumber> {
+    let exponent = i32::from(place_value);
+    let scale = 10u128.pow(exponent.unsigned_abs());
+    let (numerator, denominator) = if exponent < 0 {
+        (n.numerator as u128 * scale, n.denominator as u128)
+    } else {
+        (n.numerator as u128, n.denominator as u128 * scale)
+    };
+    let rounded = (2 * numerator + denominator) / (2 * denominator);
+    let (numerator, denominator) = if exponent < 0 { (rounded, scale) } else { (rounded * scale, 1) };
+    rational(numerator, denominator, n.negative && rounded != 0)
+}
+
+// the greatest whole number below `n`, or the least above it when `up`
+fn whole(n: RationalNumber, up: bool) -> Result<RationalNumber> {
+    let quotient = n.numerator / n.denominator;
+    let exact = n.numerator.is_multiple_of(n.denominator);
+    let away_from_zero = !exact && (up != n.negative);
+    let magnitude = if away_from_zero { quotient as u128 + 1 } else { quotient as u128 };
+    rational(magnitude, 1, n.negative && magnitude != 0)
+}
+
+fn rational(numerator: u128, denominator: u128, negative: bool) -> Result<RationalNumber> {
+    let numerator = u32::try_from(numerator).map_err(|_| Error::Overflow)?;
+    let denominator = u32::try_from(denominator).map_err(|_| Error::Overflow)?;
+    Ok(RationalNumber::new(numerator, denominator, negative, NumberDisplayFormat::Decimal(None)).simplify())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::{Expression, ExpressionValue};
+    use crate::{parse_expression, Error};
+
+    fn steps(s: &str) -> Vec<String> {
+        let mut e = parse_expression(s).unwrap();
+        let mut steps = Vec::new();
+        while e.values().len() > 1 || matches!(e.values()[0], ExpressionValue::Function(_)) {
+            e = match e.evaluate_next().unwrap() {
+                ExpressionValue::Expression(next) => next,
+                val => Expression::new(val),
+            };
+            steps.push(e.to_string());
+        }
+        steps
+    }
+
+    fn evaluate(s: &str) -> String {
+        parse_expression(s).unwrap().try_evaluate().unwrap().simplify().as_str(None)
+    }
+
+    #[test]
+    fn evaluates_functions() {
+        assert_eq!(evaluate("abs(-3)"), "3");
+        assert_eq!(evaluate("|2 - 7| + 1"), "6");
+        assert_eq!(evaluate("||-2| - |5||"), "3");
+        assert_eq!(evaluate("-|4|"), "-4");
+        assert_eq!(evaluate("sqrt(16) * 2"), "8");
+        assert_eq!(evaluate("min(3, -1, 2)"), "-1");
+        assert_eq!(evaluate("2max(1/2, 1/3)"), "1");
+        assert_eq!(evaluate("round(2.25, tenths)"), "2.3");
+        assert_eq!(evaluate("round(-2.5)"), "-3");
+        assert_eq!(evaluate("round(1234, hundreds)"), "1200");
+        assert_eq!(evaluate("floor(-7/2)"), "-4");
+        assert_eq!(evaluate("ceil(7/2)"), "4");
+        assert_eq!(evaluate("floor(3)"), "3");
+
+        assert!(parse_expression("sqrt(2)").unwrap().try_evaluate().is_err());
+        assert!(parse_expression("min(2)").is_err());
+        assert!(parse_expression("round(2, tens, ones)").is_err());
+        assert!(parse_expression("|2 - 7").is_err());
+    }
+
+    #[test]
+    fn evaluates_counting_functions() {
+        assert_eq!(evaluate("5!"), "120");
+        assert_eq!(evaluate("2^3!"), "64");
+        assert_eq!(evaluate("(2 + 1)! * 2"), "12");
+        assert_eq!(evaluate("5!/(3! * 2!)"), "10");
+        assert_eq!(evaluate("C(5, 2)"), "10");
+        assert_eq!(evaluate("P(5, 2)"), "20");
+        assert_eq!(evaluate("1/C(5, 2)"), "0.1");
+
+        assert_eq!(parse_expression("(3 - 1)! + n!").unwrap().to_string(), "(3 - 1)! + n!");
+        assert!(parse_expression("3!!").is_err());
+        assert!(parse_expression("C(5)").is_err());
+        assert!(matches!(parse_expression("(-3)!").unwrap().try_evaluate(), Err(Error::NotWholeNumber)));
+        assert_eq!(evaluate("-3!"), "-6");
+        assert_eq!(evaluate("2 - -3! * 2"), "14");
+        assert_eq!(parse_expression("-3! + 1").unwrap().to_string(), "-3! + 1");
+        assert!(matches!(parse_expression("C(2.5, 1)").unwrap().try_evaluate(), Err(Error::NotWholeNumber)));
+        assert!(matches!(parse_expression("20!").unwrap().try_evaluate(), Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn parses_root_symbols_as_calls() {
+        assert_eq!(parse_expression("√x").unwrap().to_string(), "√x");
+        assert_eq!(parse_expression("√(x + 1)").unwrap().to_string(), "√(x + 1)");
+        assert_eq!(parse_expression("sqrt(x + 1)").unwrap().to_string(), "√(x + 1)");
+        assert_eq!(parse_expression("∛(2 * 4)").unwrap().to_string(), "∛(2 * 4)");
+        assert_eq!(parse_expression("root(x, 5)").unwrap().to_string(), "root(x, 5)");
+        assert_eq!(parse_expression("root(x, 2)").unwrap().to_string(), "√x");
+        assert!(parse_expression("root(x, 1)").is_err());
+        assert_eq!(steps("∛(2 * 4) + 1"), vec!["∛8 + 1", "2 + 1", "3"]);
+        assert_eq!(evaluate("∜81 * √x".replace('x', "4").as_str()), "6");
+        assert_eq!(parse_expression("√(x + 1)").unwrap().substitute('x', 8).try_evaluate().unwrap().as_str(None), "3");
+    }
+
+    #[test]
+    fn steps_through_functions() {
+        assert_eq!(steps("abs(2 - 5) + 1"), vec!["|-3| + 1", "3 + 1", "4"]);
+        assert_eq!(steps("round(1/3 + 1, hundredths)"), vec!["round(1 1/3, hundredths)", "1.33"]);
+        assert_eq!(parse_expression("max(x, 2)").unwrap().to_string(), "max(x, 2)");
+        assert_eq!(steps("sqrt(x)".replace('x', "50").as_str()), vec!["5√2"]);
+        assert_eq!(steps("3! + C(4, 2)"), vec!["6 + C(4, 2)", "6 + 6", "12"]);
+
+        // an irrational root is as far as the steps go
+        let e = parse_expression("sqrt(2) + 1").unwrap();
+        let next = match e.evaluate_next().unwrap() {
+            ExpressionValue::Expression(next) => next,
+            val => panic!("expected an expression, got {:?}", val),
+        };
+        assert_eq!(next.to_string(), "√2 + 1");
+        assert!(next.evaluate_next().is_none());
+        let next = match parse_expression("√2 + 1").unwrap().evaluate_next().unwrap() {
+            ExpressionValue::Expression(next) => next,
+            val => panic!("expected an expression, got {:?}", val),
+        };
+        assert!(next.evaluate_next().is_none());
+        assert!(matches!(parse_expression("sqrt(2) + 1").unwrap().try_evaluate(), Err(Error::NotRational)));
+        assert_eq!(parse_expression("sqrt(x) + 1").unwrap().substitute('x', 9).try_evaluate().unwrap().as_str(None), "4");
+    }
+}
@@ -0,0 +1,628 @@
+use crate::expression::{Expression, ExpressionOperation, ExpressionValue, GroupingSymbol};
+use crate::rational_number::{NumberDisplayFormat, RangeWrapper, RationalNumber};
+use crate::{Error, PlaceValue, Result};
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RationalKind {
+    ProperFraction,
+    ImproperFraction,
+    MixedNumber,
+    TerminatingDecimal,
+    RepeatingDecimal,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Simplification {
+    Any,
+    Simplest,  // must not simplify
+    Reducible, // must simplify
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DenominatorRelationship {
+    Like,
+    Unlike,
+    Multiple, // one denominator is a multiple of the other
+}
+
+#[derive(Debug, Clone)]
+pub enum ExpressionConstraint {
+    PositiveIntegerIntermediates,
+    NoDivisionRemainders,
+    ResultInRange(RangeInclusive<RationalNumber>),
+}
+
+/// Builds random expressions for order of operations practice. Candidates are generated until one
+/// meets every constraint, so the same seed always produces the same expression.
+#[derive(Debug, Clone)]
+pub struct ExpressionGenerator {
+    operand_range: RangeWrapper<u32>,
+    exponent_range: RangeWrapper<u32>,
+    operand_count: RangeWrapper<usize>,
+    operations: Vec<ExpressionOperation>,
+    depth: usize,
+    grouping: Vec<GroupingSymbol>, // innermost first, the last one repeats
+    formats: Vec<NumberDisplayFormat>,
+    denominators: Vec<u32>,
+    constraints: Vec<ExpressionConstraint>,
+    max_attempts: usize,
+}
+
+impl Default for ExpressionGenerator {
+    fn default() -> Self {
+        ExpressionGenerator::new()
+    }
+}
+
+impl ExpressionGenerator {
+    pub fn new() -> Self {
+        ExpressionGenerator {
+            operand_range: (1..=10).into(),
+            exponent_range: (2..=3).into(),
+            operand_count: (2..=3).into(),
+            operations: vec![
+                ExpressionOperation::Addition,
+                ExpressionOperation::Subtraction,
+                ExpressionOperation::Multiplication,
+                ExpressionOperation::Division,
+            ],
+            depth: 0,
+            grouping: vec![GroupingSymbol::Parentheses, GroupingSymbol::Brackets],
+            formats: vec![NumberDisplayFormat::Decimal(None)],
+            denominators: vec![2, 3, 4, 5, 6, 8, 10, 12],
+            constraints: Vec::new(),
+            max_attempts: 1_000,
+        }
+    }
+
+    pub fn operand_range(mut self, range: impl Into<RangeWrapper<u32>>) -> Self {
+        self.operand_range = range.into();
+        self
+    }
+
+    pub fn exponent_range(mut self, range: impl Into<RangeWrapper<u32>>) -> Self {
+        self.exponent_range = range.into();
+        self
+    }
+
+    /// Number of operands in each group, at least two.
+    pub fn operand_count(mut self, range: impl Into<RangeWrapper<usize>>) -> Self {
+        self.operand_count = range.into();
+        self
+    }
+
+    pub fn operations(mut self, operations: &[ExpressionOperation]) -> Self {
+        self.operations = operations.to_vec();
+        self
+    }
+
+    /// Number of nested groups, where `0` produces an expression without grouping symbols.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn grouping(mut self, grouping: &[GroupingSymbol]) -> Self {
+        self.grouping = grouping.to_vec();
+        self
+    }
+
+    pub fn formats(mut self, formats: &[NumberDisplayFormat]) -> Self {
+        self.formats = formats.to_vec();
+        self
+    }
+
+    pub fn denominators(mut self, denominators: &[u32]) -> Self {
+        self.denominators = denominators.to_vec();
+        self
+    }
+
+    pub fn constraint(mut self, constraint: ExpressionConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Fails with `ConstraintsNotSatisfied` when no candidate meets the constraints within
+    /// `max_attempts`, or when the configuration leaves nothing to choose from, like an empty list
+    /// of operations or an empty range.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> Result<Expression> {
+        self.validate()?;
+        for _ in 0..self.max_attempts {
+            // a candidate with a number that does not fit is not counted as satisfying anything
+            if let Some(e) = self.generate_group(rng, self.depth).filter(|e| self.satisfies(e)) {
+                return Ok(e);
+            }
+        }
+        Err(Error::ConstraintsNotSatisfied)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let fractions = self
+            .formats
+            .iter()
+            .any(|f| matches!(f, NumberDisplayFormat::Fraction | NumberDisplayFormat::Mixed));
+        let exponents = self.operations.contains(&ExpressionOperation::Exponent);
+        if self.operations.is_empty()
+            || self.formats.is_empty()
+            || (self.depth > 0 && self.grouping.is_empty())
+            || (fractions && (self.denominators.is_empty() || self.denominators.contains(&0)))
+            || is_empty(&self.operand_range)
+            || is_empty(&self.operand_count)
+            || (exponents && is_empty(&self.exponent_range))
+        {
+            return Err(Error::ConstraintsNotSatisfied);
+        }
+        Ok(())
+    }
+
+    fn generate_group<R: Rng>(&self, rng: &mut R, depth: usize) -> Option<Expression> {
+        let count = gen_range(rng, &self.operand_count).max(2);
+
+        let mut operations = Vec::with_capacity(count - 1);
+        for i in 0..count - 1 {
+            // avoid stacked exponents like 2^3^2
+            let allowed: Vec<&ExpressionOperation> = self
+                .operations
+                .iter()
+                .filter(|op| i == 0 || **op != ExpressionOperation::Exponent || operations[i - 1] != ExpressionOperation::Exponent)
+                .collect();
+            let op = allowed
+                .choose(rng)
+                .map(|op| (*op).clone())
+                .or_else(|| self.operations.first().cloned())
+                .expect("no operations to choose from");
+            operations.push(op);
+        }
+
+        // a nested group never sits in an exponent
+        let nested = if depth > 0 {
+            let slots: Vec<usize> = (0..count)
+                .filter(|i| *i == 0 || operations[i - 1] != ExpressionOperation::Exponent)
+                .collect();
+            slots.choose(rng).copied()
+        } else {
+            None
+        };
+
+        let mut values: Vec<ExpressionValue> = Vec::with_capacity(count);
+        for i in 0..count {
+            let value = if Some(i) == nested {
+                let grouping = self.grouping[(depth - 1).min(self.grouping.len() - 1)];
+                self.generate_group(rng, depth - 1)?.set_grouping(grouping).into()
+            } else if i > 0 && operations[i - 1] == ExpressionOperation::Exponent {
+                gen_range(rng, &self.exponent_range).into()
+            } else {
+                self.generate_number(rng)?.into()
+            };
+            values.push(value);
+        }
+
+        let mut values = values.into_iter();
+        let mut e = Expression::new(values.next().expect("failed to get first value"));
+        for (op, value) in operations.into_iter().zip(values) {
+            e = e.push(op, value);
+        }
+        Some(e)
+    }
+
+    // None when the number does not fit
+    fn generate_number<R: Rng>(&self, rng: &mut R) -> Option<RationalNumber> {
+        let format = *self.formats.choose(rng).expect("no formats to choose from");
+        let n = match format {
+            NumberDisplayFormat::Decimal(Some(place_value)) if i32::from(place_value) < 0 => {
+                let denominator = 10u32.checked_pow(-i32::from(place_value) as u32)?;
+                let whole = gen_range(rng, &self.operand_range);
+                let numerator = whole.checked_mul(denominator)?.checked_add(rng.gen_range(1..denominator))?;
+                RationalNumber::new(numerator, denominator, false, format)
+            }
+            NumberDisplayFormat::Decimal(Some(place_value)) => {
+                let whole = gen_range(rng, &self.operand_range) as i32;
+                RationalNumber::from(crate::round_i32(whole, place_value)).set_display_format(format)
+            }
+            NumberDisplayFormat::Decimal(None) => RationalNumber::from(gen_range(rng, &self.operand_range)),
+            NumberDisplayFormat::Fraction | NumberDisplayFormat::Mixed => {
+                // the largest whole number with the largest fractional part has to fit
+                let largest = match &self.operand_range {
+                    RangeWrapper::Range(range) => range.end - 1,
+                    RangeWrapper::RangeInclusive(range) => *range.end(),
+                };
+                let denominator = self.denominators.iter().max()?;
+                largest.checked_add(1)?.checked_mul(*denominator)?;
+                RationalNumber::rand_mixed(rng, self.operand_range.clone(), &self.denominators, 0.0).set_display_format(format)
+            }
+        };
+        Some(n)
+    }
+
+    fn satisfies(&self, e: &Expression) -> bool {
+        let result = e.evaluate_with(&|op| op.priority(), &mut |op, a, b| {
+            let val = op.checked_apply(a, b)?.simplify();
+            for constraint in &self.constraints {
+                let valid = match constraint {
+                    ExpressionConstraint::PositiveIntegerIntermediates => {
+                        !val.negative && val.numerator != 0 && val.denominator == 1
+                    }
+                    ExpressionConstraint::NoDivisionRemainders => {
+                        *op != ExpressionOperation::Division || val.denominator == 1
+                    }
+                    ExpressionConstraint::ResultInRange(_) => true,
+                };
+                if !valid {
+                    return Err(Error::ConstraintsNotSatisfied);
+                }
+            }
+            Ok(val)
+        });
+
+        match result {
+            Ok(result) => self.constraints.iter().all(|constraint| match constraint {
+                ExpressionConstraint::ResultInRange(range) => range.contains(&result),
+                _ => true,
+            }),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Builds random rational numbers of the requested kinds, retrying until one meets the
+/// simplification and exclusion rules.
+#[derive(Debug, Clone)]
+pub struct RationalGenerator {
+    kinds: Vec<RationalKind>,
+    whole_range: RangeWrapper<u32>,
+    denominators: Vec<u32>,
+    place_value: Option<PlaceValue>,
+    simplification: Simplification,
+    excluded: Vec<RationalNumber>,
+    neg_prob: f64,
+    max_attempts: usize,
+}
+
+impl Default for RationalGenerator {
+    fn default() -> Self {
+        RationalGenerator::new()
+    }
+}
+
+impl RationalGenerator {
+    pub fn new() -> Self {
+        RationalGenerator {
+            kinds: vec![RationalKind::ProperFraction],
+            whole_range: (1..=10).into(),
+            denominators: vec![2, 3, 4, 5, 6, 8, 10, 12],
+            place_value: None,
+            simplification: Simplification::Any,
+            excluded: Vec::new(),
+            neg_prob: 0.0,
+            max_attempts: 1_000,
+        }
+    }
+
+    pub fn kinds(mut self, kinds: &[RationalKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    /// Whole part of improper fractions, mixed numbers and decimals.
+    pub fn whole_range(mut self, range: impl Into<RangeWrapper<u32>>) -> Self {
+        self.whole_range = range.into();
+        self
+    }
+
+    pub fn denominators(mut self, denominators: &[u32]) -> Self {
+        self.denominators = denominators.to_vec();
+        self
+    }
+
+    /// Smallest place value of terminating decimals. Without it, decimals have one to three
+    /// digits after the decimal point.
+    pub fn place_value(mut self, place_value: PlaceValue) -> Self {
+        self.place_value = Some(place_value);
+        self
+    }
+
+    pub fn simplification(mut self, simplification: Simplification) -> Self {
+        self.simplification = simplification;
+        self
+    }
+
+    pub fn exclude(mut self, values: &[RationalNumber]) -> Self {
+        self.excluded.extend_from_slice(values);
+        self
+    }
+
+    pub fn neg_prob(mut self, neg_prob: f64) -> Self {
+        self.neg_prob = neg_prob;
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Fails with `ConstraintsNotSatisfied` when no candidate meets the rules within
+    /// `max_attempts`, or when the configuration leaves nothing to choose from.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> Result<RationalNumber> {
+        self.validate()?;
+        for _ in 0..self.max_attempts {
+            if let Some(n) = self.generate_candidate(rng) {
+                if self.satisfies(&n) {
+                    return Ok(n);
+                }
+            }
+        }
+        Err(Error::ConstraintsNotSatisfied)
+    }
+
+    /// Generates two numbers whose denominators have the given relationship.
+    pub fn generate_pair<R: Rng>(&self, rng: &mut R, relationship: DenominatorRelationship) -> Result<(RationalNumber, RationalNumber)> {
+        for _ in 0..self.max_attempts {
+            let a = self.generate(rng)?;
+            let d = a.denominator;
+            let denominators: Vec<u32> = match relationship {
+                DenominatorRelationship::Like => vec![d],
+                DenominatorRelationship::Unlike => self.denominators.iter().copied().filter(|x| *x != d).collect(),
+                DenominatorRelationship::Multiple => self
+                    .denominators
+                    .iter()
+                    .copied()
+                    .filter(|x| *x != d && (x % d == 0 || d % x == 0))
+                    .collect(),
+            };
+            if denominators.is_empty() {
+                continue;
+            }
+            let generator = self.clone().denominators(&denominators).max_attempts(self.max_attempts / 10 + 1);
+            if let Ok(b) = generator.generate(rng) {
+                return Ok((a, b));
+            }
+        }
+        Err(Error::ConstraintsNotSatisfied)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let fractions = self.kinds.iter().any(|k| *k != RationalKind::TerminatingDecimal);
+        if self.kinds.is_empty()
+            || (fractions && (self.denominators.is_empty() || self.denominators.contains(&0)))
+            || is_empty(&self.whole_range)
+            || !(0.0..=1.0).contains(&self.neg_prob)
+        {
+            return Err(Error::ConstraintsNotSatisfied);
+        }
+        Ok(())
+    }
+
+    fn generate_candidate<R: Rng>(&self, rng: &mut R) -> Option<RationalNumber> {
+        let kind = *self.kinds.choose(rng).expect("no kinds to choose from");
+        let negative = rng.gen_bool(self.neg_prob);
+        let n = match kind {
+            RationalKind::ProperFraction | RationalKind::ImproperFraction | RationalKind::MixedNumber | RationalKind::RepeatingDecimal => {
+                let d = *self.denominators.choose(rng).expect("no denominators to choose from");
+                if d < 2 {
+                    return None;
+                }
+                let (whole, remainder, format) = match kind {
+                    RationalKind::ProperFraction => (0, rng.gen_range(1..d), NumberDisplayFormat::Fraction),
+                    RationalKind::ImproperFraction => (gen_range(rng, &self.whole_range).max(1), rng.gen_range(0..d), NumberDisplayFormat::Fraction),
+                    RationalKind::MixedNumber => (gen_range(rng, &self.whole_range).max(1), rng.gen_range(1..d), NumberDisplayFormat::Mixed),
+                    _ => (gen_range(rng, &self.whole_range), rng.gen_range(1..d), NumberDisplayFormat::Decimal(None)),
+                };
+                RationalNumber::new(whole * d + remainder, d, negative, format)
+            }
+            RationalKind::TerminatingDecimal => {
+                let digits = match self.place_value {
+                    Some(place_value) => (-i32::from(place_value)).max(1) as u32,
+                    None => rng.gen_range(1..=3),
+                };
+                let d = 10u32.pow(digits);
+                // the last digit is never zero, so it stays in the requested place
+                let remainder = 10 * rng.gen_range(0..d / 10) + rng.gen_range(1..10);
+                let format = NumberDisplayFormat::Decimal(self.place_value.filter(|p| i32::from(*p) < 0));
+                RationalNumber::new(gen_range(rng, &self.whole_range) * d + remainder, d, negative, format).simplify()
+            }
+        };
+        if kind == RationalKind::RepeatingDecimal && !n.repeating() {
+            return None;
+        }
+        Some(n)
+    }
+
+    // decimals are written the same whether or not their fraction is simplified
+    fn satisfies(&self, n: &RationalNumber) -> bool {
+        let simplest = crate::gcf(n.numerator, n.denominator) == 1;
+        let simplification = match self.simplification {
+            _ if matches!(n.format, NumberDisplayFormat::Decimal(_)) => true,
+            Simplification::Any => true,
+            Simplification::Simplest => simplest,
+            Simplification::Reducible => !simplest,
+        };
+        simplification && !self.excluded.contains(n)
+    }
+}
+
+fn is_empty<T: PartialOrd>(range: &RangeWrapper<T>) -> bool {
+    match range {
+        RangeWrapper::Range(range) => range.is_empty(),
+        RangeWrapper::RangeInclusive(range) => range.is_empty(),
+    }
+}
+
+fn gen_range<R: Rng, T>(rng: &mut R, range: &RangeWrapper<T>) -> T
+where
+    T: rand::distributions::uniform::SampleUniform + PartialOrd + Copy,
+{
+    match range {
+        RangeWrapper::Range(range) => rng.gen_range(range.clone()),
+        RangeWrapper::RangeInclusive(range) => rng.gen_range(range.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::{ExpressionOperation, GroupingSymbol};
+    use crate::generator::{
+        DenominatorRelationship, ExpressionConstraint, ExpressionGenerator, RationalGenerator, RationalKind, Simplification,
+    };
+    use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+    use crate::{Error, PlaceValue};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn generates_rational_numbers() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let generator = RationalGenerator::new()
+            .kinds(&[RationalKind::ProperFraction])
+            .simplification(Simplification::Reducible)
+            .exclude(&[RationalNumber::parse("1/2").unwrap()]);
+        for _ in 0..20 {
+            let n = generator.generate(&mut rng).unwrap();
+            assert!(n.numerator < n.denominator);
+            assert!(n.simplify().denominator < n.denominator);
+            assert_ne!(n, RationalNumber::parse("1/2").unwrap());
+        }
+
+        let generator = RationalGenerator::new()
+            .kinds(&[RationalKind::TerminatingDecimal])
+            .place_value(PlaceValue::Hundredths)
+            .neg_prob(1.0);
+        let n = generator.generate(&mut rng).unwrap();
+        assert!(n.negative);
+        assert_eq!(n.as_str(None).split('.').nth(1).unwrap().len(), 2);
+
+        let generator = RationalGenerator::new().kinds(&[RationalKind::RepeatingDecimal]);
+        assert!(generator.generate(&mut rng).unwrap().repeating());
+
+        let generator = RationalGenerator::new().kinds(&[RationalKind::MixedNumber]).denominators(&[1]);
+        assert!(generator.max_attempts(10).generate(&mut rng).is_err());
+
+        let n = RationalNumber::rand_mixed(&mut rng, 1..5, &[1], 0.0);
+        assert_eq!(n.denominator, 1);
+    }
+
+    #[test]
+    fn generates_terminating_decimals() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let generator = RationalGenerator::new()
+            .kinds(&[RationalKind::TerminatingDecimal])
+            .place_value(PlaceValue::Thousandths)
+            .simplification(Simplification::Simplest);
+        let mut last_digits = [0; 10];
+        for _ in 0..200 {
+            let n = generator.generate(&mut rng).unwrap();
+            assert_eq!(n, n.simplify());
+            let s = n.as_str(None);
+            assert_eq!(s.split('.').nth(1).unwrap().len(), 3);
+            last_digits[s.chars().last().unwrap().to_digit(10).unwrap() as usize] += 1;
+        }
+        assert_eq!(last_digits[0], 0);
+        assert!(last_digits[1..].iter().all(|count| *count > 5));
+    }
+
+    #[test]
+    fn rejects_empty_rational_configurations() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(RationalGenerator::new().kinds(&[]).generate(&mut rng).is_err());
+        assert!(RationalGenerator::new().denominators(&[]).generate(&mut rng).is_err());
+        assert!(RationalGenerator::new().denominators(&[0]).generate(&mut rng).is_err());
+        assert!(RationalGenerator::new().whole_range(5..5).generate(&mut rng).is_err());
+        assert!(RationalGenerator::new().neg_prob(2.0).generate(&mut rng).is_err());
+        let decimals = RationalGenerator::new().kinds(&[RationalKind::TerminatingDecimal]).denominators(&[]);
+        assert!(decimals.generate(&mut rng).is_ok());
+    }
+
+    #[test]
+    fn generates_related_pairs() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let generator = RationalGenerator::new().kinds(&[RationalKind::ProperFraction, RationalKind::MixedNumber]);
+
+        let (a, b) = generator.generate_pair(&mut rng, DenominatorRelationship::Like).unwrap();
+        assert_eq!(a.denominator, b.denominator);
+
+        let (a, b) = generator.generate_pair(&mut rng, DenominatorRelationship::Unlike).unwrap();
+        assert_ne!(a.denominator, b.denominator);
+
+        let (a, b) = generator.generate_pair(&mut rng, DenominatorRelationship::Multiple).unwrap();
+        assert_ne!(a.denominator, b.denominator);
+        assert!(a.denominator % b.denominator == 0 || b.denominator % a.denominator == 0);
+    }
+
+    #[test]
+    fn generates_reproducible_expressions() {
+        let generator = ExpressionGenerator::new()
+            .operand_range(1..=12)
+            .depth(2)
+            .formats(&[NumberDisplayFormat::Decimal(None), NumberDisplayFormat::Mixed]);
+        let a = generator.generate(&mut StdRng::seed_from_u64(7)).unwrap();
+        let b = generator.generate(&mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(a.to_string(), b.to_string());
+        assert!(a.to_string().contains('['));
+    }
+
+    #[test]
+    fn generates_with_constraints() {
+        let generator = ExpressionGenerator::new()
+            .operand_range(1..=20)
+            .operand_count(3..=4)
+            .depth(1)
+            .grouping(&[GroupingSymbol::Brackets])
+            .constraint(ExpressionConstraint::PositiveIntegerIntermediates)
+            .constraint(ExpressionConstraint::NoDivisionRemainders)
+            .constraint(ExpressionConstraint::ResultInRange(RationalNumber::from(1)..=RationalNumber::from(50)));
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let e = generator.generate(&mut rng).unwrap();
+            let result = e.evaluate().simplify();
+            assert_eq!(result.denominator, 1);
+            assert!(result >= RationalNumber::from(1) && result <= RationalNumber::from(50));
+            assert!(e.to_string().contains('['));
+        }
+
+        let generator = ExpressionGenerator::new()
+            .operations(&[ExpressionOperation::Subtraction])
+            .operand_range(1..=1)
+            .constraint(ExpressionConstraint::PositiveIntegerIntermediates)
+            .max_attempts(10);
+        assert!(generator.generate(&mut rng).is_err());
+
+        // every product overflows
+        let generator = ExpressionGenerator::new()
+            .operand_range(100000..=200000)
+            .operations(&[ExpressionOperation::Multiplication])
+            .max_attempts(10);
+        assert!(matches!(generator.generate(&mut rng), Err(Error::ConstraintsNotSatisfied)));
+
+        let generator = ExpressionGenerator::new()
+            .operand_range(4294967290..=4294967295)
+            .formats(&[NumberDisplayFormat::Mixed, NumberDisplayFormat::Decimal(Some(PlaceValue::Tenths))])
+            .max_attempts(10);
+        assert!(matches!(generator.generate(&mut rng), Err(Error::ConstraintsNotSatisfied)));
+    }
+
+    #[test]
+    fn rejects_empty_expression_configurations() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let generators = vec![
+            ExpressionGenerator::new().operations(&[]),
+            ExpressionGenerator::new().formats(&[]),
+            ExpressionGenerator::new().depth(1).grouping(&[]),
+            ExpressionGenerator::new().formats(&[NumberDisplayFormat::Fraction]).denominators(&[]),
+            ExpressionGenerator::new().operand_range(3..3),
+            ExpressionGenerator::new().operand_count(2..2),
+            ExpressionGenerator::new().operations(&[ExpressionOperation::Exponent]).exponent_range(2..2),
+        ];
+        for generator in generators {
+            assert!(matches!(generator.generate(&mut rng), Err(Error::ConstraintsNotSatisfied)));
+        }
+    }
+}
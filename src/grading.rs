@@ -0,0 +1,231 @@
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::PlaceValue;
+use serde::{Serialize, Deserialize};
+use ts_rs::TS;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GradingRubric {
+    pub format: Option<NumberDisplayFormat>,
+    pub simplest_form: bool,
+    pub tolerance: Option<RationalNumber>,
+    pub place_value: Option<PlaceValue>, // also accept the expected value rounded to this place
+    pub allow_expression: bool,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum GradeOutcome {
+    Correct,
+    PartiallyCorrect,
+    Incorrect,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum GradeReason {
+    InvalidInput,
+    IncorrectValue,
+    NotSimplified,
+    WrongFormat,
+    UnevaluatedExpression,
+}
+
+impl GradeReason {
+    pub fn as_str(&self) -> String {
+        let s = match self {
+            GradeReason::InvalidInput => "answer could not be read",
+            GradeReason::IncorrectValue => "incorrect value",
+            GradeReason::NotSimplified => "correct value, not simplified",
+            GradeReason::WrongFormat => "correct value, wrong format",
+            GradeReason::UnevaluatedExpression => "correct value, not evaluated",
+        };
+        s.to_string()
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GradeResult {
+    pub outcome: GradeOutcome,
+    pub reasons: Vec<GradeReason>,
+    pub value: Option<RationalNumber>, // what the student's answer evaluates to
+}
+
+/// Grades a student's typed answer against the expected value. Any answer with the right value
+/// is at least partially correct; the rubric decides what else is needed for full credit.
+pub fn grade(student: &str, expected: &RationalNumber, rubric: &GradingRubric) -> GradeResult {
+    let (value, is_expression) = if let Ok(n) = RationalNumber::parse(student) {
+        if n.denominator == 0 {
+            return invalid_input();
+        }
+        (n, false)
+    } else if let Ok(n) = crate::parse_expression(student).and_then(|e| e.try_evaluate()) {
+        (n, true)
+    } else {
+        return invalid_input();
+    };
+
+    if !value_matches(&value, expected, rubric) {
+        return GradeResult {
+            outcome: GradeOutcome::Incorrect,
+            reasons: vec![GradeReason::IncorrectValue],
+            value: Some(value),
+        };
+    }
+
+    let mut reasons = Vec::new();
+    if is_expression {
+        if !rubric.allow_expression {
+            reasons.push(GradeReason::UnevaluatedExpression);
+        }
+    } else {
+        if rubric.simplest_form && crate::gcf(value.numerator, value.denominator) != 1 {
+            reasons.push(GradeReason::NotSimplified);
+        }
+        if let Some(format) = rubric.format {
+            if !format_matches(student, &value, format) {
+                reasons.push(GradeReason::WrongFormat);
+            }
+        }
+    }
+
+    GradeResult {
+        outcome: if reasons.is_empty() {
+            GradeOutcome::Correct
+        } else {
+            GradeOutcome::PartiallyCorrect
+        },
+        reasons,
+        value: Some(value),
+    }
+}
+
+fn invalid_input() -> GradeResult {
+    GradeResult {
+        outcome: GradeOutcome::Incorrect,
+        reasons: vec![GradeReason::InvalidInput],
+        value: None,
+    }
+}
+
+fn value_matches(value: &RationalNumber, expected: &RationalNumber, rubric: &GradingRubric) -> bool {
+    if value == expected {
+        return true;
+    }
+    if let Some(place_value) = rubric.place_value {
+        if *value == expected.round(place_value) {
+            return true;
+        }
+    }
+    if let Some(tolerance) = rubric.tolerance {
+        if matches!(value.checked_sub(expected), Ok(difference) if difference.abs() <= tolerance.abs()) {
+            return true;
+        }
+    }
+    false
+}
+
+fn format_matches(student: &str, value: &RationalNumber, format: NumberDisplayFormat) -> bool {
+    let student = student.trim();
+    let is_whole = !student.contains('.') && !student.contains('/');
+    match format {
+        NumberDisplayFormat::Decimal(place_value) => {
+            if student.contains('/') {
+                return false;
+            }
+            match place_value {
+                Some(place_value) if i32::from(place_value) < 0 => {
+                    let digits = student.split('.').nth(1).map(|d| d.trim().len()).unwrap_or(0);
+                    digits as i32 == -i32::from(place_value)
+                }
+                _ => true,
+            }
+        }
+        NumberDisplayFormat::Fraction => {
+            // a whole number part means the answer was written as a mixed number
+            is_whole || (student.contains('/') && !(value.format == NumberDisplayFormat::Mixed && value.numerator >= value.denominator))
+        }
+        NumberDisplayFormat::Mixed => {
+            is_whole || (student.contains('/') && (value.numerator < value.denominator || value.format == NumberDisplayFormat::Mixed))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grading::{grade, GradeOutcome, GradeReason, GradingRubric};
+    use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+    use crate::PlaceValue;
+
+    #[test]
+    fn grades_values() {
+        let half = RationalNumber::parse("1/2").unwrap();
+        let rubric = GradingRubric::default();
+        assert_eq!(grade("1/2", &half, &rubric).outcome, GradeOutcome::Correct);
+        assert_eq!(grade("0.5", &half, &rubric).outcome, GradeOutcome::Correct);
+        assert_eq!(grade("3", &half, &rubric).reasons, vec![GradeReason::IncorrectValue]);
+        assert_eq!(grade("half", &half, &rubric).reasons, vec![GradeReason::InvalidInput]);
+
+        let rubric = GradingRubric { allow_expression: true, ..GradingRubric::default() };
+        assert_eq!(grade("1/3 + 1/6", &half, &rubric).outcome, GradeOutcome::Correct);
+        let result = grade("1/3 + 1/6", &half, &GradingRubric::default());
+        assert_eq!(result.outcome, GradeOutcome::PartiallyCorrect);
+        assert_eq!(result.reasons, vec![GradeReason::UnevaluatedExpression]);
+    }
+
+    #[test]
+    fn grades_forms() {
+        let half = RationalNumber::parse("1/2").unwrap();
+        let rubric = GradingRubric {
+            format: Some(NumberDisplayFormat::Fraction),
+            simplest_form: true,
+            ..GradingRubric::default()
+        };
+        let result = grade("2/4", &half, &rubric);
+        assert_eq!(result.outcome, GradeOutcome::PartiallyCorrect);
+        assert_eq!(result.reasons, vec![GradeReason::NotSimplified]);
+        assert_eq!(grade("0.5", &half, &rubric).reasons, vec![GradeReason::WrongFormat]);
+
+        let n = RationalNumber::parse("7/4").unwrap();
+        let rubric = GradingRubric { format: Some(NumberDisplayFormat::Mixed), ..GradingRubric::default() };
+        assert_eq!(grade("1 3/4", &n, &rubric).outcome, GradeOutcome::Correct);
+        assert_eq!(grade("7/4", &n, &rubric).reasons, vec![GradeReason::WrongFormat]);
+    }
+
+    #[test]
+    fn grades_rounded_answers() {
+        let third = RationalNumber::parse("1/3").unwrap();
+        let rubric = GradingRubric {
+            format: Some(NumberDisplayFormat::Decimal(Some(PlaceValue::Hundredths))),
+            place_value: Some(PlaceValue::Hundredths),
+            ..GradingRubric::default()
+        };
+        assert_eq!(grade("0.33", &third, &rubric).outcome, GradeOutcome::Correct);
+        assert_eq!(grade("0.3", &third, &rubric).outcome, GradeOutcome::Incorrect);
+
+        let rubric = GradingRubric {
+            tolerance: Some(RationalNumber::parse("0.01").unwrap()),
+            ..GradingRubric::default()
+        };
+        assert_eq!(grade("0.334", &third, &rubric).outcome, GradeOutcome::Correct);
+        assert_eq!(grade("0.35", &third, &rubric).outcome, GradeOutcome::Incorrect);
+        assert_eq!(grade("1 1/0", &third, &rubric).reasons, vec![GradeReason::InvalidInput]);
+    }
+
+    #[test]
+    fn rejects_answers_that_cannot_be_evaluated() {
+        let one = RationalNumber::from(1);
+        let rubric = GradingRubric { allow_expression: true, ..GradingRubric::default() };
+        for answer in ["2^100", "0^-1", "2^(1/2)", "100000 * 100000", "1/0"].iter() {
+            assert_eq!(grade(answer, &one, &rubric).reasons, vec![GradeReason::InvalidInput], "{}", answer);
+        }
+        assert_eq!(grade("4^(1/2) - 1", &one, &rubric).outcome, GradeOutcome::Correct);
+        assert_eq!(grade("2^-1 * 2", &one, &rubric).outcome, GradeOutcome::Correct);
+    }
+}
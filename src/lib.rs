@@ -1,12 +1,36 @@
+use crate::equation::{Equation, Relation};
 use crate::expression::{Expression};
+use crate::function::Function;
 use expression::ExpressionOperation;
 use rational_number::RationalNumber;
 use serde::{Serialize, Deserialize};
 use std::{fmt, num::ParseIntError, ops::Neg};
 use ts_rs::TS;
 
+pub mod algebra;
+pub mod canonical;
+pub mod combinatorics;
+pub mod continued_fraction;
+pub mod diagram;
+pub mod egyptian_fraction;
+pub mod equation;
+pub mod expr;
 pub mod expression;
+pub mod formatter;
+pub mod fraction_model;
+pub mod function;
+#[cfg(feature = "rand")]
+pub mod generator;
+pub mod grading;
+pub mod matrix;
+pub mod misconception;
+pub mod number_line;
+pub mod polynomial;
+pub mod quadratic;
+pub mod radical;
 pub mod rational_number;
+pub mod stern_brocot;
+mod svg;
 
 #[derive(Debug, Clone)]
 pub enum Error {
@@ -14,6 +38,25 @@ pub enum Error {
     ParseExpression,
     ParseInt(ParseIntError),
     DenominatorCannotBeZero,
+    NotFinite,
+    Overflow,
+    InvalidContinuedFraction,
+    NotProperFraction,
+    EgyptianFractionNotFound,
+    NotPositive,
+    ConstraintsNotSatisfied,
+    UnboundVariable(char),
+    NotPolynomial,
+    ParseEquation,
+    NotLinear,
+    DimensionMismatch,
+    SingularMatrix,
+    NotQuadratic,
+    NotReal,
+    NotRational,
+    UnlikeRadicals,
+    NotWholeNumber,
+    InvalidRange,
 }
 
 impl fmt::Display for Error {
@@ -23,6 +66,25 @@ impl fmt::Display for Error {
             Error::ParseInt(e) => write!(f, "{}", e),
             Error::ParseRationalExpression =>  write!(f, "ParseRationalExpressionError"),
             Error::DenominatorCannotBeZero => write!(f, "DenominatorCannotBeZero"),
+            Error::NotFinite => write!(f, "NotFinite"),
+            Error::Overflow => write!(f, "Overflow"),
+            Error::InvalidContinuedFraction => write!(f, "InvalidContinuedFraction"),
+            Error::NotProperFraction => write!(f, "NotProperFraction"),
+            Error::EgyptianFractionNotFound => write!(f, "EgyptianFractionNotFound"),
+            Error::NotPositive => write!(f, "NotPositive"),
+            Error::ConstraintsNotSatisfied => write!(f, "ConstraintsNotSatisfied"),
+            Error::UnboundVariable(c) => write!(f, "UnboundVariable({})", c),
+            Error::NotPolynomial => write!(f, "NotPolynomial"),
+            Error::ParseEquation => write!(f, "ParseEquationError"),
+            Error::NotLinear => write!(f, "NotLinear"),
+            Error::DimensionMismatch => write!(f, "DimensionMismatch"),
+            Error::SingularMatrix => write!(f, "SingularMatrix"),
+            Error::NotQuadratic => write!(f, "NotQuadratic"),
+            Error::NotReal => write!(f, "NotReal"),
+            Error::NotRational => write!(f, "NotRational"),
+            Error::UnlikeRadicals => write!(f, "UnlikeRadicals"),
+            Error::NotWholeNumber => write!(f, "NotWholeNumber"),
+            Error::InvalidRange => write!(f, "InvalidRange"),
         }
     }
 }
@@ -156,6 +218,21 @@ pub fn lcm(a: u32, b: u32) -> u32 {
     (a / gcf(a, b)) * b
 }
 
+// `gcf` and `lcm` for intermediate values that do not fit in a u32
+pub(crate) fn gcf_u128(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcf_u128(b, a % b)
+    }
+}
+
+pub(crate) fn lcm_u128(a: u128, b: u128) -> u128 {
+    (a / gcf_u128(a, b)) * b
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
 pub enum Operation {
     Multiplication,
@@ -194,17 +271,9 @@ pub fn round_i64(num: i64, place_value: PlaceValue) -> i64 {
 pub fn round_f64(num: f64, place_value: PlaceValue) -> String {
     let digits: i32 = i32::from(place_value);
     if digits < 0 {
-        round::round(num, digits.neg()).to_string()
+        format!("{:.*}", digits.neg() as usize, round::round(num, digits.neg()))
     } else {
-        match place_value {
-            PlaceValue::Tenths => format!("{:.1}", num),
-            PlaceValue::Hundredths => format!("{:.2}", num),
-            PlaceValue::Thousandths => format!("{:.3}", num),
-            PlaceValue::TenThousandths => format!("{:.4}", num),
-            PlaceValue::HundredThousandths => format!("{:.5}", num),
-            PlaceValue::Millionths => format!("{:.6}", num),
-            _ => format!("{}", round_i64(num as i64, place_value)),
-        }
+        format!("{}", round_i64(num as i64, place_value))
     }
 }
 
@@ -216,12 +285,27 @@ pub fn parse_expression(s: &str) -> Result<Expression> {
     index += i;
 
     let mut expr = Expression::new(val);
-    while index < s.len() {
+    while !s[index..].trim().is_empty() {
         if let Some((i, op)) = expression::parse_first_operation(&s[index..]) {
             index += i;
             let (i, val) = expression::parse_first_expression_value(&s[index..])?;
             index += i;
             expr = expr.push(op, val);
+        } else if let Some(i) = function::parse_factorial(&s[index..]) {
+            index += i;
+            expr = expr.apply_postfix(Function::Factorial)?;
+        } else if let Some((i, call)) = function::parse_first_function(&s[index..])? {
+            // implied multiplication
+            index += i;
+            expr = expr.push(ExpressionOperation::Multiplication, call);
+        } else if let Some((i, call)) = expression::parse_first_root(&s[index..])? {
+            // implied multiplication
+            index += i;
+            expr = expr.push(ExpressionOperation::Multiplication, call);
+        } else if let Some((i, val)) = expression::parse_first_variable(&s[index..]) {
+            // implied multiplication
+            index += i;
+            expr = expr.push(ExpressionOperation::Multiplication, val);
         } else if let Some((i, val)) = expression::parse_first_expression(&s[index..])? {
             // implied multiplication
             index += i;
@@ -234,9 +318,56 @@ pub fn parse_expression(s: &str) -> Result<Expression> {
     Ok(expr)
 }
 
+/// Parses two expressions separated by exactly one of `=`, `<`, `<=`, `>`, `>=`, `≤` or `≥`.
+pub fn parse_equation(s: &str) -> Result<Equation> {
+    let relations = [
+        ("<=", Relation::LessOrEqual),
+        ("≤", Relation::LessOrEqual),
+        (">=", Relation::GreaterOrEqual),
+        ("≥", Relation::GreaterOrEqual),
+        ("<", Relation::Less),
+        (">", Relation::Greater),
+        ("=", Relation::Equal),
+    ];
+    let mut found = None;
+    let mut i = 0;
+    while i < s.len() {
+        match relations.iter().find(|(symbol, _)| s[i..].starts_with(symbol)) {
+            Some((symbol, relation)) => {
+                if found.is_some() {
+                    return Err(Error::ParseEquation);
+                }
+                found = Some((i, symbol.len(), *relation));
+                i += symbol.len();
+            }
+            None => i += s[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(1),
+        }
+    }
+
+    let (i, len, relation) = found.ok_or(Error::ParseEquation)?;
+    let left = parse_expression(&s[..i])?;
+    let right = parse_expression(&s[i + len..])?;
+    Ok(Equation::new(left, relation, right))
+}
+
+pub trait WrapNumber {
+    fn wrap_if_neg(self) -> String;
+}
+
+impl WrapNumber for RationalNumber {
+    fn wrap_if_neg(self) -> String {
+        if self.negative {
+            format!("({})", self)
+        } else {
+            self.to_string()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::parse_expression;
+    use crate::expression::Expression;
+    use crate::{parse_expression, Error};
 
     #[test]
     fn parses_strings() {
@@ -257,19 +388,20 @@ mod tests {
 
         let e = parse_expression("3(3 + 1) - (2 + 1)^3").unwrap();
         assert_eq!(e.evaluate().simplify().as_str(None), "-15");
-    }
-}
 
-pub trait WrapNumber {
-    fn wrap_if_neg(self) -> String;
-}
+        let e = parse_expression("-(2 + 1) * 2 ").unwrap();
+        assert_eq!(e.evaluate().simplify().as_str(None), "-6");
+    }
 
-impl WrapNumber for RationalNumber {
-    fn wrap_if_neg(self) -> String {
-        if self.negative {
-            format!("({})", self)
-        } else {
-            self.to_string()
-        }
+    #[test]
+    fn parses_radicals() {
+        let e = parse_expression("sqrt(50)").unwrap();
+        assert_eq!(e.to_string(), "√50");
+        assert_eq!(Expression::new(e.evaluate_next().unwrap()).to_string(), "(5√2)");
+        assert_eq!(parse_expression("√50").unwrap().to_string(), "√50");
+        assert_eq!(parse_expression("3√2 + ∛(2 * 4)").unwrap().to_string(), "3 * √2 + ∛(2 * 4)");
+        assert_eq!(parse_expression("-√9").unwrap().evaluate().simplify().as_str(None), "-3");
+        assert!(parse_expression("sqrt(2)").unwrap().try_evaluate().is_err());
+        assert!(matches!(parse_expression("√(-4)").unwrap().try_evaluate(), Err(Error::NotReal)));
     }
-}
\ No newline at end of file
+}
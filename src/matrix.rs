@@ -0,0 +1,468 @@
+use crate::equation::{Equation, Relation};
+use crate::polynomial::Polynomial;
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::{Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use ts_rs::TS;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Matrix<T> {
+    entries: Vec<Vec<T>>, // rows, all the same length
+}
+
+// deserialized entries, checked by `Matrix::new` before they become a matrix
+#[derive(Deserialize)]
+struct MatrixEntries<T> {
+    entries: Vec<Vec<T>>,
+}
+
+impl<'de, T: Deserialize<'de> + Clone> Deserialize<'de> for Matrix<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let m = MatrixEntries::deserialize(deserializer)?;
+        Matrix::new(m.entries).map_err(de::Error::custom)
+    }
+}
+
+impl<T: Clone> Matrix<T> {
+    pub fn new(entries: Vec<Vec<T>>) -> Result<Self> {
+        let columns = entries.first().map(|r| r.len()).unwrap_or(0);
+        if columns == 0 || entries.iter().any(|r| r.len() != columns) {
+            return Err(Error::DimensionMismatch);
+        }
+        Ok(Matrix { entries })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn columns(&self) -> usize {
+        self.entries[0].len()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Option<&T> {
+        self.entries.get(row).and_then(|r| r.get(column))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.entries[row]
+    }
+
+    pub fn transpose(&self) -> Self {
+        Matrix {
+            entries: (0..self.columns())
+                .map(|c| self.entries.iter().map(|r| r[c].clone()).collect())
+                .collect(),
+        }
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "value")]
+pub enum RowOperation {
+    Swap(usize, usize),
+    Scale { row: usize, factor: RationalNumber },
+    AddMultiple { target: usize, source: usize, factor: RationalNumber }, // target += factor * source
+}
+
+impl RowOperation {
+    /// Describes the operation with rows numbered from 1, the way they are written on a worksheet.
+    pub fn as_str(&self) -> String {
+        match self {
+            RowOperation::Swap(a, b) => format!("swap R{} and R{}", a + 1, b + 1),
+            RowOperation::Scale { row, factor } => format!("multiply R{} by {}", row + 1, factor),
+            RowOperation::AddMultiple { target, source, factor } if factor.negative => {
+                format!("subtract {} times R{} from R{}", factor.abs(), source + 1, target + 1)
+            }
+            RowOperation::AddMultiple { target, source, factor } => {
+                format!("add {} times R{} to R{}", factor, source + 1, target + 1)
+            }
+        }
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MatrixStep {
+    pub operation: RowOperation,
+    pub matrix: Matrix<RationalNumber>, // after the operation
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "value")]
+pub enum SystemSolution {
+    Unique(Vec<RationalNumber>),
+    NoSolution,
+    // one solution with every free variable set to zero
+    Infinite { particular: Vec<RationalNumber>, free: Vec<usize> },
+}
+
+impl Matrix<RationalNumber> {
+    pub fn zero(rows: usize, columns: usize) -> Self {
+        Matrix {
+            entries: vec![vec![RationalNumber::from(0); columns]; rows],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Matrix::zero(n, n);
+        for i in 0..n {
+            m.entries[i][i] = RationalNumber::from(1);
+        }
+        m
+    }
+
+    pub fn multiply(&self, other: &Matrix<RationalNumber>) -> Result<Self> {
+        if self.columns() != other.rows() {
+            return Err(Error::DimensionMismatch);
+        }
+        let mut m = Matrix::zero(self.rows(), other.columns());
+        for r in 0..self.rows() {
+            for c in 0..other.columns() {
+                let mut sum = RationalNumber::from(0);
+                for k in 0..self.columns() {
+                    sum = exact(sum.checked_add(&self.entries[r][k].checked_mul(&other.entries[k][c])?)?);
+                }
+                m.entries[r][c] = sum;
+            }
+        }
+        Ok(m)
+    }
+
+    pub fn rref(&self) -> Result<Self> {
+        Ok(self.rref_steps()?.pop().map(|s| s.matrix).unwrap_or_else(|| self.clone()))
+    }
+
+    /// Gauss-Jordan elimination, one row operation per step. Fails with `Overflow` when an entry
+    /// along the way does not fit.
+    pub fn rref_steps(&self) -> Result<Vec<MatrixStep>> {
+        self.reduce(self.columns())
+    }
+
+    pub fn rank(&self) -> Result<usize> {
+        let rref = self.rref()?;
+        Ok(rref.entries.iter().filter(|r| r.iter().any(|n| n.numerator != 0)).count())
+    }
+
+    pub fn determinant(&self) -> Result<RationalNumber> {
+        if self.rows() != self.columns() {
+            return Err(Error::DimensionMismatch);
+        }
+        let steps = self.rref_steps()?;
+        let reduced = steps.last().map(|s| &s.matrix).unwrap_or(self);
+        if (0..self.rows()).any(|i| reduced.entries[i][i] != RationalNumber::from(1)) {
+            return Ok(RationalNumber::from(0));
+        }
+
+        // only swaps and scaling change the determinant, and the result is the identity
+        let mut det = RationalNumber::from(1);
+        for step in &steps {
+            match step.operation {
+                RowOperation::Swap(..) => det = det.neg(),
+                RowOperation::Scale { factor, .. } => det = exact(det.checked_div(&factor)?),
+                RowOperation::AddMultiple { .. } => {}
+            }
+        }
+        Ok(det)
+    }
+
+    pub fn inverse(&self) -> Result<Self> {
+        let n = self.rows();
+        if n != self.columns() {
+            return Err(Error::DimensionMismatch);
+        }
+        let identity = Matrix::identity(n);
+        let augmented = Matrix {
+            entries: self
+                .entries
+                .iter()
+                .zip(&identity.entries)
+                .map(|(a, b)| a.iter().chain(b).cloned().collect())
+                .collect(),
+        };
+        let reduced = augmented.reduce(n)?.pop().map(|s| s.matrix).unwrap_or(augmented);
+        if (0..n).any(|i| reduced.entries[i][i] != RationalNumber::from(1)) {
+            return Err(Error::SingularMatrix);
+        }
+        Ok(Matrix {
+            entries: reduced.entries.into_iter().map(|r| r[n..].to_vec()).collect(),
+        })
+    }
+
+    /// Solves `self * x = constants` by reducing the augmented matrix.
+    pub fn solve(&self, constants: &[RationalNumber]) -> Result<(SystemSolution, Vec<MatrixStep>)> {
+        if constants.len() != self.rows() {
+            return Err(Error::DimensionMismatch);
+        }
+        let n = self.columns();
+        let augmented = Matrix {
+            entries: self
+                .entries
+                .iter()
+                .zip(constants)
+                .map(|(r, c)| r.iter().chain(std::iter::once(c)).cloned().collect())
+                .collect(),
+        };
+        let steps = augmented.reduce(n)?;
+        let reduced = steps.last().map(|s| &s.matrix).unwrap_or(&augmented);
+
+        let mut pivots = Vec::new();
+        for row in &reduced.entries {
+            match row[..n].iter().position(|x| x.numerator != 0) {
+                Some(c) => pivots.push((c, row[n])),
+                None if row[n].numerator != 0 => return Ok((SystemSolution::NoSolution, steps)),
+                None => {}
+            }
+        }
+
+        let mut values = vec![RationalNumber::from(0); n];
+        for (c, value) in &pivots {
+            values[*c] = *value;
+        }
+        let free: Vec<usize> = (0..n).filter(|c| pivots.iter().all(|(p, _)| p != c)).collect();
+        let solution = if free.is_empty() {
+            SystemSolution::Unique(values)
+        } else {
+            SystemSolution::Infinite { particular: values, free }
+        };
+        Ok((solution, steps))
+    }
+
+    // reduces the first `columns` columns to reduced row echelon form
+    fn reduce(&self, columns: usize) -> Result<Vec<MatrixStep>> {
+        let mut m = self.clone();
+        for row in m.entries.iter_mut() {
+            for n in row.iter_mut() {
+                *n = exact(*n);
+            }
+        }
+        let mut steps = Vec::new();
+        let mut apply = |m: &mut Matrix<RationalNumber>, operation: RowOperation| -> Result<()> {
+            m.apply(&operation)?;
+            steps.push(MatrixStep {
+                operation,
+                matrix: m.clone(),
+            });
+            Ok(())
+        };
+
+        let mut pivot_row = 0;
+        for c in 0..columns {
+            if pivot_row == m.rows() {
+                break;
+            }
+            let r = match (pivot_row..m.rows()).find(|r| m.entries[*r][c].numerator != 0) {
+                Some(r) => r,
+                None => continue,
+            };
+            if r != pivot_row {
+                apply(&mut m, RowOperation::Swap(pivot_row, r))?;
+            }
+            let pivot = m.entries[pivot_row][c];
+            if pivot != RationalNumber::from(1) {
+                let factor = exact(pivot.reciprocal());
+                apply(&mut m, RowOperation::Scale { row: pivot_row, factor })?;
+            }
+            for r in 0..m.rows() {
+                let x = m.entries[r][c];
+                if r != pivot_row && x.numerator != 0 {
+                    let factor = exact(x.neg());
+                    apply(
+                        &mut m,
+                        RowOperation::AddMultiple {
+                            target: r,
+                            source: pivot_row,
+                            factor,
+                        },
+                    )?;
+                }
+            }
+            pivot_row += 1;
+        }
+        Ok(steps)
+    }
+
+    fn apply(&mut self, operation: &RowOperation) -> Result<()> {
+        match *operation {
+            RowOperation::Swap(a, b) => self.entries.swap(a, b),
+            RowOperation::Scale { row, factor } => {
+                for n in self.entries[row].iter_mut() {
+                    *n = exact(n.checked_mul(&factor)?);
+                }
+            }
+            RowOperation::AddMultiple { target, source, factor } => {
+                for c in 0..self.columns() {
+                    let n = self.entries[target][c].checked_add(&self.entries[source][c].checked_mul(&factor)?)?;
+                    self.entries[target][c] = exact(n);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = self
+            .entries
+            .iter()
+            .map(|r| format!("[{}]", r.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(", ")))
+            .collect();
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+/// Solves a system of linear equations, with the variables in alphabetical order.
+pub fn solve_system(equations: &[Equation]) -> Result<(Vec<char>, SystemSolution, Vec<MatrixStep>)> {
+    let mut variables: Vec<char> = equations.iter().flat_map(|e| e.variables()).collect();
+    variables.sort_unstable();
+    variables.dedup();
+
+    let mut coefficients = Vec::with_capacity(equations.len());
+    let mut constants = Vec::with_capacity(equations.len());
+    for equation in equations {
+        // only equations form a linear system
+        if equation.relation != Relation::Equal {
+            return Err(Error::NotLinear);
+        }
+        let left = Polynomial::try_from(&equation.left).map_err(|_| Error::NotLinear)?;
+        let right = Polynomial::try_from(&equation.right).map_err(|_| Error::NotLinear)?;
+        let p = left.checked_sub(&right)?;
+        if p.degree().unwrap_or(0) > 1 {
+            return Err(Error::NotLinear);
+        }
+        let coefficient = |v: Option<char>| {
+            p.terms()
+                .iter()
+                .find(|t| match v {
+                    Some(v) => t.variables == [(v, 1)],
+                    None => t.variables.is_empty(),
+                })
+                .map(|t| t.coefficient)
+                .unwrap_or_else(|| RationalNumber::from(0))
+        };
+        coefficients.push(variables.iter().map(|v| coefficient(Some(*v))).collect());
+        constants.push(coefficient(None).neg());
+    }
+
+    if variables.is_empty() {
+        return Err(Error::DimensionMismatch);
+    }
+    let (solution, steps) = Matrix::new(coefficients)?.solve(&constants)?;
+    Ok((variables, solution, steps))
+}
+
+// simplified, and written as a fraction unless it is a whole number
+fn exact(n: RationalNumber) -> RationalNumber {
+    let n = n.simplify();
+    if n.numerator == 0 {
+        RationalNumber::from(0)
+    } else if n.denominator == 1 {
+        n.set_display_format(NumberDisplayFormat::Decimal(None))
+    } else {
+        n.set_display_format(NumberDisplayFormat::Fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matrix::{solve_system, Matrix, SystemSolution};
+    use crate::parse_equation;
+    use crate::rational_number::RationalNumber;
+    use crate::Error;
+
+    fn matrix(rows: &[&[i32]]) -> Matrix<RationalNumber> {
+        Matrix::new(rows.iter().map(|r| r.iter().map(|n| RationalNumber::from(*n)).collect()).collect()).unwrap()
+    }
+
+    #[test]
+    fn reduces_matrices() {
+        let m = matrix(&[&[2, 1, -1], &[-3, -1, 2], &[-2, 1, 2]]);
+        assert_eq!(m.rref().unwrap(), Matrix::identity(3));
+        assert_eq!(m.rank().unwrap(), 3);
+        assert_eq!(m.determinant().unwrap().to_string(), "-1");
+        assert_eq!(m.multiply(&m.inverse().unwrap()).unwrap(), Matrix::identity(3));
+
+        let m = matrix(&[&[1, 2], &[2, 4]]);
+        assert_eq!(m.rref().unwrap().to_string(), "[1, 2]\n[0, 0]");
+        assert_eq!(m.rank().unwrap(), 1);
+        assert_eq!(m.determinant().unwrap().to_string(), "0");
+        assert!(m.inverse().is_err());
+
+        let m = matrix(&[&[0, 3], &[2, 1]]);
+        let steps: Vec<String> = m.rref_steps().unwrap().iter().map(|s| s.operation.as_str()).collect();
+        assert_eq!(
+            steps,
+            vec!["swap R1 and R2", "multiply R1 by 1/2", "multiply R2 by 1/3", "subtract 1/2 times R2 from R1"]
+        );
+        assert_eq!(m.determinant().unwrap().to_string(), "-6");
+        assert_eq!(m.inverse().unwrap().to_string(), "[-1/6, 1/2]\n[1/3, 0]");
+
+        assert!(Matrix::new(vec![vec![1], vec![1, 2]]).is_err());
+        assert!(matrix(&[&[1, 2]]).determinant().is_err());
+    }
+
+    #[test]
+    fn reports_overflow_while_reducing() {
+        let m = Matrix::new(
+            ["1/65537", "1/65539", "1/65543", "1/65551"]
+                .chunks(2)
+                .map(|r| r.iter().map(|n| RationalNumber::parse(n).unwrap()).collect())
+                .collect(),
+        )
+        .unwrap();
+        assert!(matches!(m.rref(), Err(Error::Overflow)));
+        assert!(matches!(m.rank(), Err(Error::Overflow)));
+        assert!(matches!(m.determinant(), Err(Error::Overflow)));
+        assert!(matches!(m.inverse(), Err(Error::Overflow)));
+        assert!(matches!(m.solve(&[RationalNumber::from(1), RationalNumber::from(1)]), Err(Error::Overflow)));
+        assert!(matches!(m.multiply(&m), Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn deserializes_only_rectangular_matrices() {
+        let m = matrix(&[&[1, 2], &[3, 4]]);
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(serde_json::from_str::<Matrix<RationalNumber>>(&json).unwrap(), m);
+
+        assert!(serde_json::from_str::<Matrix<i32>>(r#"{"entries": []}"#).is_err());
+        assert!(serde_json::from_str::<Matrix<i32>>(r#"{"entries": [[]]}"#).is_err());
+        assert!(serde_json::from_str::<Matrix<i32>>(r#"{"entries": [[1], [1, 2]]}"#).is_err());
+        assert_eq!(serde_json::from_str::<Matrix<i32>>(r#"{"entries": [[1, 2]]}"#).unwrap().columns(), 2);
+    }
+
+    #[test]
+    fn solves_systems() {
+        let equations = vec![parse_equation("x + y = 4").unwrap(), parse_equation("2x - y = 3").unwrap()];
+        let (variables, solution, steps) = solve_system(&equations).unwrap();
+        assert_eq!(variables, vec!['x', 'y']);
+        assert_eq!(
+            solution,
+            SystemSolution::Unique(vec![RationalNumber::parse("7/3").unwrap(), RationalNumber::parse("5/3").unwrap()])
+        );
+        assert_eq!(steps.last().unwrap().matrix.to_string(), "[1, 0, 7/3]\n[0, 1, 5/3]");
+
+        let equations = vec![parse_equation("x + y = 1").unwrap(), parse_equation("2x + 2y = 3").unwrap()];
+        assert_eq!(solve_system(&equations).unwrap().1, SystemSolution::NoSolution);
+
+        let equations = vec![parse_equation("x + y = 1").unwrap(), parse_equation("2x + 2y = 2").unwrap()];
+        assert_eq!(
+            solve_system(&equations).unwrap().1,
+            SystemSolution::Infinite {
+                particular: vec![RationalNumber::from(1), RationalNumber::from(0)],
+                free: vec![1],
+            }
+        );
+
+        assert!(solve_system(&[parse_equation("x^2 = 1").unwrap()]).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::expression::{Expression, ExpressionOperation, ExpressionValue, OperationPriority};
+use crate::rational_number::RationalNumber;
+use crate::{Error, Result};
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use ts_rs::TS;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Misconception {
+    LeftToRight,                  // 2 + 3 * 4 = 20
+    AdditionBeforeMultiplication, // 2 * 3 + 4 = 14
+    AddNumeratorsAndDenominators, // 1/2 + 1/3 = 2/5
+    ForgetDistributeNegative,     // 10 - (4 + 1) = 7
+    ExponentAsMultiplication,     // 3^2 = 6
+    DropRemainder,                // 7 -: 2 = 3
+    SignError,                    // 3 - -2 = 1, -2 * -3 = -6
+    FlipDividend,                 // 1/2 -: 3/4 = 2/1 * 3/4
+    DecimalMisalignment,          // 2.5 + 0.25 = 0.50
+}
+
+impl Misconception {
+    pub fn all() -> Vec<Misconception> {
+        vec![
+            Misconception::LeftToRight,
+            Misconception::AdditionBeforeMultiplication,
+            Misconception::AddNumeratorsAndDenominators,
+            Misconception::ForgetDistributeNegative,
+            Misconception::ExponentAsMultiplication,
+            Misconception::DropRemainder,
+            Misconception::SignError,
+            Misconception::FlipDividend,
+            Misconception::DecimalMisalignment,
+        ]
+    }
+
+    pub fn as_str(&self) -> String {
+        let s = match self {
+            Misconception::LeftToRight => "left to right",
+            Misconception::AdditionBeforeMultiplication => "addition before multiplication",
+            Misconception::AddNumeratorsAndDenominators => "add numerators and denominators",
+            Misconception::ForgetDistributeNegative => "forget to distribute negative",
+            Misconception::ExponentAsMultiplication => "exponent as multiplication",
+            Misconception::DropRemainder => "drop remainder",
+            Misconception::SignError => "sign error",
+            Misconception::FlipDividend => "flip the dividend",
+            Misconception::DecimalMisalignment => "misaligned decimal places",
+        };
+        s.to_string()
+    }
+
+    /// Evaluates `expr` the way a student holding this misconception would. Returns `None` if the
+    /// evaluation divides by zero or overflows.
+    pub fn evaluate(&self, expr: &Expression) -> Option<RationalNumber> {
+        match self {
+            Misconception::LeftToRight => evaluate(expr, |_| 0, |op, a, b| op.checked_apply(a, b)),
+            Misconception::AdditionBeforeMultiplication => evaluate(
+                expr,
+                |op| match op {
+                    ExpressionOperation::Addition | ExpressionOperation::Subtraction => 1,
+                    ExpressionOperation::Multiplication | ExpressionOperation::Division => 0,
+                    _ => op.priority(),
+                },
+                |op, a, b| op.checked_apply(a, b),
+            ),
+            Misconception::AddNumeratorsAndDenominators => evaluate(expr, ExpressionOperation::priority, |op, a, b| match op {
+                ExpressionOperation::Addition if a.denominator != 1 || b.denominator != 1 => {
+                    let n = a.signed_numerator() + b.signed_numerator();
+                    let numerator = u32::try_from(n.unsigned_abs()).map_err(|_| Error::Overflow)?;
+                    let denominator = a.denominator.checked_add(b.denominator).ok_or(Error::Overflow)?;
+                    Ok(RationalNumber::new(numerator, denominator, n < 0, a.format))
+                }
+                _ => op.checked_apply(a, b),
+            }),
+            Misconception::ForgetDistributeNegative => {
+                evaluate(&without_distributed_negatives(expr), ExpressionOperation::priority, |op, a, b| op.checked_apply(a, b))
+            }
+            Misconception::ExponentAsMultiplication => evaluate(expr, ExpressionOperation::priority, |op, a, b| match op {
+                ExpressionOperation::Exponent => a.checked_mul(&b),
+                _ => op.checked_apply(a, b),
+            }),
+            Misconception::DropRemainder => evaluate(expr, ExpressionOperation::priority, |op, a, b| match op {
+                ExpressionOperation::Division => {
+                    let q = a.checked_div(&b)?.simplify();
+                    Ok(RationalNumber::new(q.numerator / q.denominator, 1, q.negative && q.numerator >= q.denominator, a.format))
+                }
+                _ => op.checked_apply(a, b),
+            }),
+            Misconception::SignError => evaluate(expr, ExpressionOperation::priority, |op, a, b| match op {
+                ExpressionOperation::Subtraction if b.negative => a.checked_add(&b),
+                ExpressionOperation::Multiplication | ExpressionOperation::Division if a.negative && b.negative => {
+                    Ok(op.checked_apply(a, b)?.neg())
+                }
+                _ => op.checked_apply(a, b),
+            }),
+            Misconception::FlipDividend => evaluate(expr, ExpressionOperation::priority, |op, a, b| match op {
+                ExpressionOperation::Division if a.numerator != 0 => a.reciprocal().checked_mul(&b),
+                _ => op.checked_apply(a, b),
+            }),
+            Misconception::DecimalMisalignment => evaluate(expr, ExpressionOperation::priority, |op, a, b| match op {
+                ExpressionOperation::Addition | ExpressionOperation::Subtraction => {
+                    let (a, b) = misaligned(&a, &b).unwrap_or((a, b));
+                    op.checked_apply(a, b)
+                }
+                _ => op.checked_apply(a, b),
+            }),
+        }
+    }
+}
+
+/// Misconceptions that reproduce a wrong `student_answer` to `expression`. A correct answer has
+/// nothing to diagnose.
+pub fn diagnose(expression: &Expression, student_answer: &RationalNumber) -> Vec<Misconception> {
+    match expression.try_evaluate() {
+        Ok(correct) if correct != *student_answer => Misconception::all()
+            .into_iter()
+            .filter(|m| m.evaluate(expression).as_ref() == Some(student_answer))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Distractor {
+    pub misconception: Misconception,
+    pub value: RationalNumber,
+}
+
+impl Expression {
+    /// Wrong answers produced by common misconceptions, excluding the correct answer and keeping
+    /// only the first misconception that leads to each value.
+    pub fn distractors(&self) -> Vec<Distractor> {
+        let correct = match self.try_evaluate() {
+            Ok(n) => n,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut distractors: Vec<Distractor> = Vec::new();
+        for misconception in Misconception::all() {
+            if let Some(value) = misconception.evaluate(self) {
+                if value != correct && distractors.iter().all(|d| d.value != value) {
+                    distractors.push(Distractor { misconception, value });
+                }
+            }
+        }
+        distractors
+    }
+}
+
+fn evaluate<P, F>(expr: &Expression, priority: P, apply: F) -> Option<RationalNumber>
+where
+    P: Fn(&ExpressionOperation) -> OperationPriority,
+    F: Fn(&ExpressionOperation, RationalNumber, RationalNumber) -> Result<RationalNumber>,
+{
+    expr.evaluate_with(&priority, &mut |op, a, b| Ok(apply(op, a, b)?.simplify())).ok()
+}
+
+// a - (b + c) becomes a - b + c. Only a group that is the whole subtracted term is opened, so
+// the group in a - (b + c)^2 or a - (b + c) * d stays.
+fn without_distributed_negatives(expr: &Expression) -> Expression {
+    let mut values = expr.values().iter();
+    let mut e = Expression::new(ungroup_values(values.next().expect("failed to get first value")));
+    for (i, (op, val)) in expr.operations().iter().zip(values).enumerate() {
+        let whole_term = matches!(
+            expr.operations().get(i + 1),
+            None | Some(ExpressionOperation::Addition) | Some(ExpressionOperation::Subtraction)
+        );
+        match val {
+            ExpressionValue::Expression(group) if *op == ExpressionOperation::Subtraction && whole_term => {
+                let group = without_distributed_negatives(group);
+                let mut group_values = group.values().iter();
+                e = e.push(op.clone(), group_values.next().expect("failed to get first value").clone());
+                for (group_op, group_val) in group.operations().iter().zip(group_values) {
+                    e = e.push(group_op.clone(), group_val.clone());
+                }
+            }
+            _ => e = e.push(op.clone(), ungroup_values(val)),
+        }
+    }
+    e.set_grouping(expr.grouping())
+}
+
+fn ungroup_values(val: &ExpressionValue) -> ExpressionValue {
+    match val {
+        ExpressionValue::Expression(e) => without_distributed_negatives(e).into(),
+        _ => val.clone(),
+    }
+}
+
+// digits after the decimal point of a terminating decimal
+fn decimal_places(n: &RationalNumber) -> Option<u32> {
+    let (s, repeating_digit_count) = n.as_decimal_str();
+    if repeating_digit_count.is_some() {
+        None
+    } else {
+        Some(s.split('.').nth(1).map(|d| d.len() as u32).unwrap_or(0))
+    }
+}
+
+// two terminating decimals with their last digits lined up instead of their decimal points, so
+// 2.5 and 0.25 become 0.25 and 0.25, or `None` when they already line up or do not fit
+fn misaligned(a: &RationalNumber, b: &RationalNumber) -> Option<(RationalNumber, RationalNumber)> {
+    let (pa, pb) = (decimal_places(a)?, decimal_places(b)?);
+    if pa == pb || pa.max(pb) >= 10 {
+        return None;
+    }
+    let denominator = 10u32.pow(pa.max(pb));
+    let a = RationalNumber::new(digits(a, pa)?, denominator, a.negative, a.format);
+    let b = RationalNumber::new(digits(b, pb)?, denominator, b.negative, b.format);
+    Some((a, b))
+}
+
+// all digits of a terminating decimal as a whole number, so 2.05 becomes 205
+fn digits(n: &RationalNumber, places: u32) -> Option<u32> {
+    let scaled = n.numerator as u64 * 10u64.pow(places) / n.denominator as u64;
+    u32::try_from(scaled).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::misconception::{diagnose, Misconception};
+    use crate::parse_expression;
+    use crate::rational_number::RationalNumber;
+
+    fn distractors(s: &str) -> Vec<(Misconception, String)> {
+        parse_expression(s)
+            .unwrap()
+            .distractors()
+            .into_iter()
+            .map(|d| (d.misconception, d.value.as_str(None)))
+            .collect()
+    }
+
+    #[test]
+    fn generates_distractors() {
+        assert_eq!(distractors("2 + 3 * 4"), vec![(Misconception::LeftToRight, "20".to_string())]);
+        assert_eq!(
+            distractors("1/2 + 1/3"),
+            vec![(Misconception::AddNumeratorsAndDenominators, "2/5".to_string())]
+        );
+        assert_eq!(
+            distractors("10 - (4 + 1)"),
+            vec![(Misconception::ForgetDistributeNegative, "7".to_string())]
+        );
+        assert_eq!(distractors("3^2"), vec![(Misconception::ExponentAsMultiplication, "6".to_string())]);
+        assert_eq!(
+            distractors("7 -: 2"),
+            vec![
+                (Misconception::DropRemainder, "3".to_string()),
+                (Misconception::FlipDividend, "0.bar285714".to_string()),
+            ]
+        );
+        assert_eq!(
+            distractors("14 -: 4 * 2 - 1"),
+            vec![
+                (Misconception::AdditionBeforeMultiplication, "3.5".to_string()),
+                (Misconception::DropRemainder, "5".to_string()),
+                (Misconception::FlipDividend, "-0.bar428571".to_string()),
+            ]
+        );
+        assert!(distractors("4 -: 0").is_empty());
+
+        let forgets_negative = |s: &str| distractors(s).iter().any(|(m, _)| *m == Misconception::ForgetDistributeNegative);
+        assert!(!forgets_negative("10 - (1 + 2)^2"));
+        assert!(!forgets_negative("10 - (4 + 1) * 2"));
+        assert_eq!(
+            distractors("10 - (4 + 1) + 2"),
+            vec![(Misconception::ForgetDistributeNegative, "9".to_string())]
+        );
+    }
+    #[test]
+    fn diagnoses_answers() {
+        let diagnose_str = |e: &str, n: &str| diagnose(&parse_expression(e).unwrap(), &RationalNumber::parse(n).unwrap());
+        assert_eq!(
+            diagnose_str("2 + 3 * 4", "20"),
+            vec![Misconception::LeftToRight, Misconception::AdditionBeforeMultiplication]
+        );
+        assert_eq!(diagnose_str("2 + 3 * 4", "14"), vec![]);
+        assert_eq!(diagnose_str("3 - -2", "1"), vec![Misconception::SignError]);
+        assert_eq!(diagnose_str("-2 * -3", "-6"), vec![Misconception::SignError]);
+        assert_eq!(diagnose_str("1/2 -: 3/4", "3/2"), vec![Misconception::FlipDividend]);
+        assert_eq!(diagnose_str("2.5 + 0.25", "0.5"), vec![Misconception::DecimalMisalignment]);
+        assert_eq!(diagnose_str("1/2 + 1/3", "2/5"), vec![Misconception::AddNumeratorsAndDenominators]);
+        assert_eq!(diagnose_str("2 + 3 * 4", "7"), vec![]);
+        assert_eq!(diagnose_str("0.123457 + 0.1", "0.123458"), vec![Misconception::DecimalMisalignment]);
+        assert_eq!(diagnose_str("4294.967295 + 1000 + 0.1", "1"), vec![]);
+        assert_eq!(diagnose_str("100000 * 100000 + 1", "1"), vec![]);
+    }
+}
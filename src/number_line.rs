@@ -0,0 +1,410 @@
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::svg::{self, Svg};
+use crate::{Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use ts_rs::TS;
+
+const MAX_TICKS: u32 = 1000;
+const MARGIN: f64 = 30.0;
+const TICK_SPACING: f64 = 56.0;
+const JUMP_HEIGHT: f64 = 30.0;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum MarkStyle {
+    Point,
+    OpenCircle,   // an endpoint that is not included, as in x < 3
+    ClosedCircle, // an endpoint that is included, as in x ≤ 3
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct NumberLineMark {
+    pub value: RationalNumber,
+    pub style: MarkStyle,
+}
+
+/// An arrow from `from` to `from + by`, labeled with `by`.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Jump {
+    pub from: RationalNumber,
+    pub by: RationalNumber,
+}
+
+/// A number line from `start` to `end` with a tick every `interval`, drawn as SVG or TikZ. Ticks
+/// on whole numbers are longer, and every tick is labeled in the label format, which defaults to
+/// the interval's format.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberLine {
+    start: RationalNumber,
+    end: RationalNumber,
+    interval: RationalNumber,
+    label_format: NumberDisplayFormat,
+    marks: Vec<NumberLineMark>,
+    jumps: Vec<Jump>,
+}
+
+// deserialized fields, with the range checked by `NumberLine::new`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NumberLineFields {
+    start: RationalNumber,
+    end: RationalNumber,
+    interval: RationalNumber,
+    label_format: NumberDisplayFormat,
+    marks: Vec<NumberLineMark>,
+    jumps: Vec<Jump>,
+}
+
+impl<'de> Deserialize<'de> for NumberLine {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let f = NumberLineFields::deserialize(deserializer)?;
+        let mut line = NumberLine::new(f.start, f.end, f.interval).map_err(de::Error::custom)?;
+        line.label_format = f.label_format;
+        line.marks = f.marks;
+        line.jumps = f.jumps;
+        Ok(line)
+    }
+}
+
+impl NumberLine {
+    /// Fails with `Overflow` when there would be more than 1000 ticks or a tick does not fit.
+    pub fn new(start: RationalNumber, end: RationalNumber, interval: RationalNumber) -> Result<Self> {
+        if start >= end {
+            return Err(Error::InvalidRange);
+        }
+        if interval.negative || interval.numerator == 0 {
+            return Err(Error::NotPositive);
+        }
+        tick_values(&start, &end, &interval)?;
+        Ok(NumberLine {
+            start,
+            end,
+            interval,
+            label_format: interval.format,
+            marks: Vec::new(),
+            jumps: Vec::new(),
+        })
+    }
+
+    pub fn label_format(mut self, format: NumberDisplayFormat) -> Self {
+        self.label_format = format;
+        self
+    }
+
+    pub fn mark(mut self, value: RationalNumber, style: MarkStyle) -> Self {
+        self.marks.push(NumberLineMark { value, style });
+        self
+    }
+
+    pub fn point(self, value: RationalNumber) -> Self {
+        self.mark(value, MarkStyle::Point)
+    }
+
+    pub fn open_circle(self, value: RationalNumber) -> Self {
+        self.mark(value, MarkStyle::OpenCircle)
+    }
+
+    pub fn closed_circle(self, value: RationalNumber) -> Self {
+        self.mark(value, MarkStyle::ClosedCircle)
+    }
+
+    /// A jump to the right for a positive `by` and to the left for a negative one, so
+    /// `1/2 - 3/4` is `jump(1/2, -3/4)`.
+    pub fn jump(mut self, from: RationalNumber, by: RationalNumber) -> Self {
+        self.jumps.push(Jump { from, by });
+        self
+    }
+
+    pub fn ticks(&self) -> Vec<RationalNumber> {
+        tick_values(&self.start, &self.end, &self.interval).expect("failed to get ticks checked by new")
+    }
+
+    pub fn to_svg(&self) -> String {
+        let length = self.position(&self.end) * TICK_SPACING;
+        let axis = if self.jumps.is_empty() { 20.0 } else { 40.0 + JUMP_HEIGHT };
+        let x = |v: &RationalNumber| MARGIN + self.position(v) * TICK_SPACING;
+        let jump_end = |jump: &Jump| x(&jump.from) + ratio(&jump.by, &self.interval) * TICK_SPACING;
+        let mut svg = Svg::new(length + 2.0 * MARGIN, axis + 45.0);
+
+        let (left, right) = (MARGIN / 2.0, length + 1.5 * MARGIN);
+        svg.line((left, axis), (right, axis), "black", 1.5);
+        arrowhead(&mut svg, (left, axis), (-1.0, 0.0));
+        arrowhead(&mut svg, (right, axis), (1.0, 0.0));
+        for tick in self.ticks() {
+            let size = if tick.denominator == 1 { 10.0 } else { 6.0 };
+            svg.line((x(&tick), axis - size), (x(&tick), axis + size), "black", 1.0);
+            label(&mut svg, (x(&tick), axis + 24.0), &tick.as_str(Some(self.label_format)));
+        }
+
+        for jump in &self.jumps {
+            let (x1, x2, y) = (x(&jump.from), jump_end(jump), axis - 6.0);
+            let control = ((x1 + x2) / 2.0, y - 2.0 * JUMP_HEIGHT);
+            svg.path(
+                &format!(
+                    "M {} {} Q {} {} {} {}",
+                    svg::number(x1),
+                    svg::number(y),
+                    svg::number(control.0),
+                    svg::number(control.1),
+                    svg::number(x2),
+                    svg::number(y)
+                ),
+                "none",
+                "black",
+            );
+            let (dx, dy) = (x2 - control.0, y - control.1);
+            let length = (dx * dx + dy * dy).sqrt();
+            arrowhead(&mut svg, (x2, y), (dx / length, dy / length));
+            label(&mut svg, (control.0, y - JUMP_HEIGHT - 12.0), &self.jump_label(&jump.by, false));
+        }
+
+        for mark in &self.marks {
+            let center = (x(&mark.value), axis);
+            match mark.style {
+                MarkStyle::Point => svg.circle(center, 4.0, "black", "black"),
+                MarkStyle::OpenCircle => svg.circle(center, 6.0, "white", "black"),
+                MarkStyle::ClosedCircle => svg.circle(center, 6.0, "black", "black"),
+            }
+        }
+        svg.finish()
+    }
+
+    /// The number line as a `tikzpicture` with one unit per tick and labels in math mode.
+    pub fn to_tikz(&self) -> String {
+        let end = svg::number(self.position(&self.end) + 0.5);
+        let mut s = String::from("\\begin{tikzpicture}[x=1.2cm]\n");
+        s.push_str(&format!("  \\draw[<->] (-0.5,0) -- ({},0);\n", end));
+        for tick in self.ticks() {
+            let size = if tick.denominator == 1 { "0.2" } else { "0.12" };
+            s.push_str(&format!(
+                "  \\draw ({x},{size}) -- ({x},-{size}) node[below] {{${label}$}};\n",
+                x = svg::number(self.position(&tick)),
+                size = size,
+                label = latex(&tick, self.label_format)
+            ));
+        }
+        for jump in &self.jumps {
+            let bend = if jump.by.negative { "right" } else { "left" };
+            s.push_str(&format!(
+                "  \\draw[->] ({},0.25) to[bend {}=60] node[above] {{${}$}} ({},0.25);\n",
+                svg::number(self.position(&jump.from)),
+                bend,
+                self.jump_label(&jump.by, true),
+                svg::number(self.position(&jump.from) + ratio(&jump.by, &self.interval))
+            ));
+        }
+        for mark in &self.marks {
+            let (command, radius) = match mark.style {
+                MarkStyle::Point => ("\\fill", "2pt"),
+                MarkStyle::OpenCircle => ("\\draw[fill=white]", "3pt"),
+                MarkStyle::ClosedCircle => ("\\fill", "3pt"),
+            };
+            s.push_str(&format!(
+                "  {} ({},0) circle ({});\n",
+                command,
+                svg::number(self.position(&mark.value)),
+                radius
+            ));
+        }
+        s.push_str("\\end{tikzpicture}\n");
+        s
+    }
+
+    // the number of ticks from the start to a value
+    fn position(&self, value: &RationalNumber) -> f64 {
+        let (n, d) = difference(&self.start, value);
+        (n as f64 * self.interval.denominator as f64) / (d as f64 * self.interval.numerator as f64)
+    }
+
+    // a signed label, in TeX for TikZ
+    fn jump_label(&self, by: &RationalNumber, tex: bool) -> String {
+        let by_abs = by.abs().simplify();
+        let (minus, label) = if tex {
+            ("-", latex(&by_abs, self.label_format))
+        } else {
+            ("−", by_abs.as_str(Some(self.label_format)))
+        };
+        format!("{}{}", if by.negative { minus } else { "+" }, label)
+    }
+}
+
+// `to - from` as a numerator and denominator, which fit since both parts are u32
+fn difference(from: &RationalNumber, to: &RationalNumber) -> (i128, i128) {
+    (
+        to.signed_numerator() as i128 * from.denominator as i128 - from.signed_numerator() as i128 * to.denominator as i128,
+        to.denominator as i128 * from.denominator as i128,
+    )
+}
+
+// a / b, without the u32 products of dividing exactly
+fn ratio(a: &RationalNumber, b: &RationalNumber) -> f64 {
+    (a.signed_numerator() as f64 * b.denominator as f64) / (a.denominator as f64 * b.signed_numerator() as f64)
+}
+
+// every tick from `start` up to `end`, computed exactly since the interval may be too small for
+// their count to fit in a u32 and a tick may be too large for a RationalNumber
+fn tick_values(start: &RationalNumber, end: &RationalNumber, interval: &RationalNumber) -> Result<Vec<RationalNumber>> {
+    if start.denominator == 0 || end.denominator == 0 || interval.denominator == 0 {
+        return Err(Error::DenominatorCannotBeZero);
+    }
+    let (length, denominator) = difference(start, end);
+    let count = (length * interval.denominator as i128 / (denominator * interval.numerator as i128)) as u128;
+    if count > MAX_TICKS as u128 {
+        return Err(Error::Overflow);
+    }
+    // start + i * interval over the product of their denominators
+    let denominator = start.denominator as i128 * interval.denominator as i128;
+    (0..=count as i128)
+        .map(|i| {
+            let numerator = start.signed_numerator() as i128 * interval.denominator as i128
+                + i * interval.numerator as i128 * start.denominator as i128;
+            let f = crate::gcf_u128(numerator.unsigned_abs(), denominator as u128).max(1) as i128;
+            Ok(RationalNumber::from_parts(numerator / f, denominator / f)?.simplify())
+        })
+        .collect()
+}
+
+// an SVG label, with the repeating digits of a decimal under a bar
+fn label(svg: &mut Svg, at: (f64, f64), text: &str) {
+    match text.find("bar") {
+        Some(i) => svg.overlined_text(at, &text[..i], &text[i + 3..]),
+        None => svg.text(at, text),
+    }
+}
+
+// a filled triangle with its tip at `tip`, pointing in `direction`
+fn arrowhead(svg: &mut Svg, tip: (f64, f64), direction: (f64, f64)) {
+    let base = (tip.0 - 8.0 * direction.0, tip.1 - 8.0 * direction.1);
+    let (px, py) = (-4.0 * direction.1, 4.0 * direction.0);
+    svg.path(
+        &format!(
+            "M {} {} L {} {} L {} {} Z",
+            svg::number(tip.0),
+            svg::number(tip.1),
+            svg::number(base.0 + px),
+            svg::number(base.1 + py),
+            svg::number(base.0 - px),
+            svg::number(base.1 - py)
+        ),
+        "black",
+        "none",
+    );
+}
+
+fn latex(n: &RationalNumber, format: NumberDisplayFormat) -> String {
+    let sign = if n.negative && n.numerator != 0 { "-" } else { "" };
+    let whole = n.numerator / n.denominator;
+    let remainder = n.numerator % n.denominator;
+    match format {
+        NumberDisplayFormat::Decimal(_) => {
+            let s = n.as_str(Some(format));
+            match s.find("bar") {
+                Some(i) => format!("{}\\overline{{{}}}", &s[..i], &s[i + 3..]),
+                None => s,
+            }
+        }
+        _ if n.denominator == 1 || n.numerator == 0 => n.as_str(Some(NumberDisplayFormat::Mixed)),
+        NumberDisplayFormat::Mixed if whole > 0 && remainder > 0 => {
+            format!("{}{}\\frac{{{}}}{{{}}}", sign, whole, remainder, n.denominator)
+        }
+        _ => format!("{}\\frac{{{}}}{{{}}}", sign, n.numerator, n.denominator),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::number_line::NumberLine;
+    use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+    use crate::Error;
+
+    fn n(s: &str) -> RationalNumber {
+        RationalNumber::parse(s).unwrap()
+    }
+
+    #[test]
+    fn finds_ticks() {
+        let line = NumberLine::new(n("-1/2"), n("1"), n("1/4")).unwrap();
+        let ticks: Vec<String> = line.ticks().iter().map(|t| t.as_str(None)).collect();
+        assert_eq!(ticks, vec!["-1/2", "-1/4", "0", "1/4", "1/2", "3/4", "1"]);
+
+        let line = NumberLine::new(n("0"), n("1"), n("0.3")).unwrap();
+        assert_eq!(line.ticks().len(), 4);
+
+        assert!(matches!(NumberLine::new(n("1"), n("1"), n("1/4")), Err(Error::InvalidRange)));
+        assert!(matches!(NumberLine::new(n("0"), n("1"), n("0")), Err(Error::NotPositive)));
+        assert!(matches!(NumberLine::new(n("0"), n("1000"), n("1/2")), Err(Error::Overflow)));
+        assert!(matches!(NumberLine::new(n("0"), n("70000"), n("1/70000")), Err(Error::Overflow)));
+        assert!(matches!(NumberLine::new(n("-4294967295"), n("4294967295"), n("1")), Err(Error::Overflow)));
+        assert_eq!(NumberLine::new(n("0"), n("1/70"), n("1/70000")).unwrap().ticks().len(), 1001);
+        // 1/65537 + 65521/65539 does not fit
+        assert!(matches!(NumberLine::new(n("1/65537"), n("2"), n("65521/65539")), Err(Error::Overflow)));
+        let ticks = NumberLine::new(n("4294967290"), n("4294967295"), n("2")).unwrap().ticks();
+        assert_eq!(ticks.last().unwrap(), &n("4294967294"));
+    }
+
+    #[test]
+    fn draws_svg() {
+        let svg = NumberLine::new(n("0"), n("2"), n("1/4"))
+            .unwrap()
+            .label_format(NumberDisplayFormat::Mixed)
+            .jump(n("1/4"), n("1 1/4"))
+            .jump(n("1 1/2"), n("-3/4"))
+            .point(n("1/4"))
+            .open_circle(n("2"))
+            .to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"508\" height=\"115\""));
+        assert_eq!(svg.matches("<line").count(), 10);
+        assert!(svg.contains(">1 3/4</text>"));
+        assert!(svg.contains(">+1 1/4</text>"));
+        assert!(svg.contains(">−3/4</text>"));
+        assert!(svg.contains("<path d=\"M 86 64 Q 226 4 366 64\" fill=\"none\" stroke=\"black\"/>"));
+        assert!(svg.contains("<circle cx=\"478\" cy=\"70\" r=\"6\" fill=\"white\" stroke=\"black\"/>"));
+
+        let svg = NumberLine::new(n("0"), n("1"), n("1/3"))
+            .unwrap()
+            .label_format(NumberDisplayFormat::Decimal(None))
+            .jump(n("0"), n("1/3"))
+            .to_svg();
+        assert!(svg.contains(">0.<tspan text-decoration=\"overline\">3</tspan></text>"));
+        assert!(svg.contains(">+0.<tspan text-decoration=\"overline\">3</tspan></text>"));
+        assert!(!svg.contains("bar"));
+    }
+
+    #[test]
+    fn deserializes_only_valid_ranges() {
+        let line = NumberLine::new(n("0"), n("2"), n("1/2")).unwrap().point(n("1"));
+        let json = serde_json::to_string(&line).unwrap();
+        assert_eq!(serde_json::from_str::<NumberLine>(&json).unwrap(), line);
+
+        let zero_interval = json.replace(r#""interval":{"numerator":1"#, r#""interval":{"numerator":0"#);
+        assert_ne!(zero_interval, json);
+        assert!(serde_json::from_str::<NumberLine>(&zero_interval).is_err());
+    }
+
+    #[test]
+    fn writes_tikz() {
+        let tikz = NumberLine::new(n("0"), n("1"), n("1/2"))
+            .unwrap()
+            .jump(n("1"), n("-1/2"))
+            .closed_circle(n("1/2"))
+            .to_tikz();
+        assert_eq!(
+            tikz,
+            "\\begin{tikzpicture}[x=1.2cm]\n  \\draw[<->] (-0.5,0) -- (2.5,0);\n  \\draw (0,0.2) -- (0,-0.2) node[below] {$0$};\n  \\draw (1,0.12) -- (1,-0.12) node[below] {$\\frac{1}{2}$};\n  \\draw (2,0.2) -- (2,-0.2) node[below] {$1$};\n  \\draw[->] (2,0.25) to[bend right=60] node[above] {$-\\frac{1}{2}$} (1,0.25);\n  \\fill (1,0) circle (3pt);\n\\end{tikzpicture}\n"
+        );
+
+        let tikz = NumberLine::new(n("0"), n("1"), n("1/3"))
+            .unwrap()
+            .label_format(NumberDisplayFormat::Decimal(None))
+            .to_tikz();
+        assert!(tikz.contains("{$0.\\overline{3}$}"));
+    }
+}
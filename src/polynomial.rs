@@ -0,0 +1,561 @@
+use crate::expression::{variable_char, Expression, ExpressionOperation, ExpressionValue};
+use crate::rational_number::RationalNumber;
+use crate::{Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::{fmt, ops};
+use ts_rs::TS;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Term {
+    pub coefficient: RationalNumber,
+    #[ts(type = "Array<[string, number]>")]
+    pub variables: Vec<(char, u32)>, // sorted by variable, exponents above zero
+}
+
+impl Term {
+    pub fn degree(&self) -> u32 {
+        self.variables.iter().map(|(_, e)| e).sum()
+    }
+
+    pub fn exponent(&self, variable: char) -> u32 {
+        self.variables.iter().find(|(v, _)| *v == variable).map(|(_, e)| *e).unwrap_or(0)
+    }
+
+    /// Writes the term as a product, leaving out a coefficient of 1 and writing -1 as a sign, such
+    /// as `3 * x^2 * y` or `-x^2 * y`.
+    pub fn to_expression(&self) -> Expression {
+        let negated = self.coefficient == RationalNumber::from(-1) && !self.variables.is_empty();
+        let mut e: Option<Expression> = None;
+        if self.variables.is_empty() || !(negated || self.coefficient == RationalNumber::from(1)) {
+            e = Some(Expression::new(self.coefficient));
+        }
+        for (i, (v, exp)) in self.variables.iter().enumerate() {
+            let val = ExpressionValue::Variable(v.to_string());
+            let mut next = match e {
+                Some(e) => e.push(ExpressionOperation::Multiplication, val),
+                None if negated => Expression::new(-1).push(ExpressionOperation::Multiplication, val),
+                None => Expression::new(val),
+            };
+            if *exp > 1 {
+                next = next.push(ExpressionOperation::Exponent, *exp);
+            }
+            // grouped the way the parser reads -x^2
+            if negated && i == 0 {
+                next = Expression::new(next);
+            }
+            e = Some(next);
+        }
+        e.expect("failed to build term")
+    }
+
+    fn mul(&self, other: &Term) -> Result<Term> {
+        let mut variables = self.variables.clone();
+        for (v, e) in &other.variables {
+            match variables.iter_mut().find(|(w, _)| w == v) {
+                Some((_, exponent)) => *exponent = exponent.checked_add(*e).ok_or(Error::Overflow)?,
+                None => variables.push((*v, *e)),
+            }
+        }
+        variables.sort_unstable();
+        Ok(Term {
+            coefficient: self.coefficient.checked_mul(&other.coefficient)?.simplify(),
+            variables,
+        })
+    }
+
+    // None unless every variable in `divisor` appears here with at least the same exponent
+    fn div(&self, divisor: &Term) -> Result<Option<Term>> {
+        if divisor.variables.iter().any(|(v, e)| self.exponent(*v) < *e) {
+            return Ok(None);
+        }
+        let variables = self
+            .variables
+            .iter()
+            .map(|(v, e)| (*v, e - divisor.exponent(*v)))
+            .filter(|(_, e)| *e > 0)
+            .collect();
+        Ok(Some(Term {
+            coefficient: self.coefficient.checked_div(&divisor.coefficient)?.simplify(),
+            variables,
+        }))
+    }
+
+    /// Graded lexicographic order: higher total degree first, then higher powers of earlier
+    /// letters, so `x^2` comes before `xy` and `xy` before `y^2`.
+    fn cmp_monomial(&self, other: &Term) -> Ordering {
+        self.degree().cmp(&other.degree()).then_with(|| {
+            let mut variables: Vec<char> = self.variables.iter().chain(&other.variables).map(|(v, _)| *v).collect();
+            variables.sort_unstable();
+            variables.dedup();
+            variables
+                .into_iter()
+                .map(|v| self.exponent(v).cmp(&other.exponent(v)))
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Polynomial {
+    terms: Vec<Term>, // combined, nonzero and in descending graded lexicographic order
+}
+
+// deserialized terms, checked to already be in the order `from_terms` leaves them
+#[derive(Deserialize)]
+struct PolynomialTerms {
+    terms: Vec<Term>,
+}
+
+impl<'de> Deserialize<'de> for Polynomial {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let p = PolynomialTerms::deserialize(deserializer)?;
+        let normalized = p.terms.iter().all(|t| {
+            t.coefficient.numerator != 0
+                && t.coefficient.denominator != 0
+                && t.variables.iter().all(|(_, e)| *e > 0)
+                && t.variables.windows(2).all(|w| w[0].0 < w[1].0)
+        }) && p.terms.windows(2).all(|w| w[0].cmp_monomial(&w[1]) == Ordering::Greater);
+        if normalized {
+            Ok(Polynomial { terms: p.terms })
+        } else {
+            Err(de::Error::custom(Error::NotPolynomial))
+        }
+    }
+}
+
+impl Polynomial {
+    pub fn zero() -> Self {
+        Polynomial { terms: Vec::new() }
+    }
+
+    pub fn constant<T: Into<RationalNumber>>(n: T) -> Self {
+        Polynomial::from_term(Term {
+            coefficient: n.into(),
+            variables: Vec::new(),
+        })
+    }
+
+    pub fn variable(variable: char) -> Self {
+        Polynomial::from_term(Term {
+            coefficient: RationalNumber::from(1),
+            variables: vec![(variable, 1)],
+        })
+    }
+
+    pub fn from_term(term: Term) -> Self {
+        Polynomial::from_terms(vec![term]).expect("failed to build a polynomial from one term")
+    }
+
+    /// Combines like terms, which fails with `Overflow` when their coefficients add up to a
+    /// number that does not fit.
+    pub fn from_terms(mut terms: Vec<Term>) -> Result<Self> {
+        for term in terms.iter_mut() {
+            term.variables.retain(|(_, e)| *e > 0);
+            term.variables.sort_unstable();
+        }
+        terms.sort_by(|a, b| b.cmp_monomial(a));
+
+        let mut combined: Vec<Term> = Vec::with_capacity(terms.len());
+        for term in terms {
+            match combined.last_mut() {
+                Some(last) if last.variables == term.variables => {
+                    last.coefficient = last.coefficient.checked_add(&term.coefficient)?.simplify();
+                }
+                _ => combined.push(term),
+            }
+        }
+        combined.retain(|t| t.coefficient.numerator != 0);
+        for term in combined.iter_mut() {
+            term.coefficient = term.coefficient.simplify();
+        }
+        Ok(Polynomial { terms: combined })
+    }
+
+    pub fn terms(&self) -> &Vec<Term> {
+        &self.terms
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Total degree of the highest term. The zero polynomial has no degree.
+    pub fn degree(&self) -> Option<u32> {
+        self.terms.first().map(|t| t.degree())
+    }
+
+    pub fn degree_in(&self, variable: char) -> Option<u32> {
+        self.terms.iter().map(|t| t.exponent(variable)).max()
+    }
+
+    pub fn leading_term(&self) -> Option<&Term> {
+        self.terms.first()
+    }
+
+    pub fn leading_coefficient(&self) -> RationalNumber {
+        self.terms.first().map(|t| t.coefficient).unwrap_or_else(|| RationalNumber::from(0))
+    }
+
+    pub fn variables(&self) -> Vec<char> {
+        let mut variables: Vec<char> = self.terms.iter().flat_map(|t| t.variables.iter().map(|(v, _)| *v)).collect();
+        variables.sort_unstable();
+        variables.dedup();
+        variables
+    }
+
+    fn constant_value(&self) -> Option<RationalNumber> {
+        match self.terms.as_slice() {
+            [] => Some(RationalNumber::from(0)),
+            [t] if t.variables.is_empty() => Some(t.coefficient),
+            _ => None,
+        }
+    }
+
+    pub fn checked_add(&self, rhs: &Polynomial) -> Result<Polynomial> {
+        Polynomial::from_terms([self.terms.clone(), rhs.terms.clone()].concat())
+    }
+
+    pub fn checked_sub(&self, rhs: &Polynomial) -> Result<Polynomial> {
+        self.checked_add(&-rhs.clone())
+    }
+
+    pub fn checked_mul(&self, rhs: &Polynomial) -> Result<Polynomial> {
+        let mut terms = Vec::with_capacity(self.terms.len() * rhs.terms.len());
+        for a in &self.terms {
+            for b in &rhs.terms {
+                terms.push(a.mul(b)?);
+            }
+        }
+        Polynomial::from_terms(terms)
+    }
+
+    pub fn pow(&self, exp: u32) -> Result<Self> {
+        let mut result = Polynomial::constant(1);
+        for _ in 0..exp {
+            result = result.checked_mul(self)?;
+        }
+        Ok(result)
+    }
+
+    /// Long division, returning the quotient and remainder. With more than one variable the
+    /// leading terms follow graded lexicographic order, so the remainder has no term divisible
+    /// by the divisor's leading term.
+    pub fn div_rem(&self, divisor: &Polynomial) -> Result<(Polynomial, Polynomial)> {
+        let lead = divisor.leading_term().ok_or(Error::DenominatorCannotBeZero)?;
+        let mut quotient = Vec::new();
+        let mut remainder = Vec::new();
+        let mut p = self.clone();
+        while let Some(term) = p.terms.first().cloned() {
+            let next = match term.div(lead)? {
+                Some(q) => {
+                    let next = p.checked_sub(&divisor.checked_mul(&Polynomial::from_term(q.clone()))?)?;
+                    quotient.push(q);
+                    next
+                }
+                None => {
+                    remainder.push(term);
+                    Polynomial { terms: p.terms[1..].to_vec() }
+                }
+            };
+            p = next;
+        }
+        Ok((Polynomial::from_terms(quotient)?, Polynomial::from_terms(remainder)?))
+    }
+
+    pub fn evaluate(&self, values: &HashMap<char, RationalNumber>) -> Result<RationalNumber> {
+        let mut total = RationalNumber::from(0);
+        for term in &self.terms {
+            let mut n = term.coefficient;
+            for (v, e) in &term.variables {
+                let value = values.get(v).ok_or(Error::UnboundVariable(*v))?;
+                for _ in 0..*e {
+                    n = n.checked_mul(value)?.simplify();
+                }
+            }
+            total = total.checked_add(&n)?.simplify();
+        }
+        Ok(total)
+    }
+
+    /// Writes the polynomial as a flat expression, such as `3 * x^2 + 2 * x - 5`.
+    pub fn to_expression(&self) -> Expression {
+        let mut e: Option<Expression> = None;
+        for term in &self.terms {
+            let coefficient = match &e {
+                Some(_) => term.coefficient.abs(),
+                None => term.coefficient,
+            };
+            let term_expr = Term {
+                coefficient,
+                variables: term.variables.clone(),
+            }
+            .to_expression();
+            e = Some(match e {
+                None => term_expr,
+                Some(e) => {
+                    let op = if term.coefficient.negative {
+                        ExpressionOperation::Subtraction
+                    } else {
+                        ExpressionOperation::Addition
+                    };
+                    let mut values = term_expr.values().iter();
+                    let mut e = e.push(op, values.next().expect("failed to get first value").clone());
+                    for (op, val) in term_expr.operations().iter().zip(values) {
+                        e = e.push(op.clone(), val.clone());
+                    }
+                    e
+                }
+            });
+        }
+        e.unwrap_or_else(|| Expression::new(0))
+    }
+}
+
+impl TryFrom<&Expression> for Polynomial {
+    type Error = Error;
+
+    /// Exponents must be whole numbers and divisors nonzero constants. Fails with `Overflow` when a
+    /// coefficient does not fit.
+    fn try_from(expression: &Expression) -> Result<Self> {
+        expression.fold(
+            &mut |val, _| match val {
+                ExpressionValue::Number(n) => Ok(Polynomial::constant(*n)),
+                ExpressionValue::Variable(v) => Ok(Polynomial::variable(variable_char(v))),
+                ExpressionValue::Expression(e) => Polynomial::try_from(e),
+                ExpressionValue::Radical(r) => match RationalNumber::try_from(*r) {
+                    Ok(n) => Ok(Polynomial::constant(n)),
+                    Err(_) => Err(Error::NotPolynomial),
+                },
+                ExpressionValue::Function(call) => match Expression::new(call.clone()).try_evaluate() {
+                    Ok(n) => Ok(Polynomial::constant(n)),
+                    Err(_) => Err(Error::NotPolynomial),
+                },
+            },
+            &ExpressionOperation::priority,
+            &mut |op, a, b| match op {
+                ExpressionOperation::Addition => a.checked_add(&b),
+                ExpressionOperation::Subtraction => a.checked_sub(&b),
+                ExpressionOperation::Multiplication => a.checked_mul(&b),
+                ExpressionOperation::Division => match b.constant_value() {
+                    Some(n) if n.numerator != 0 => a.checked_mul(&Polynomial::constant(n.reciprocal())),
+                    _ => Err(Error::NotPolynomial),
+                },
+                ExpressionOperation::Exponent => match b.constant_value() {
+                    Some(n) if n.denominator == 1 && !n.negative => a.pow(n.numerator),
+                    _ => Err(Error::NotPolynomial),
+                },
+            },
+        )
+    }
+}
+
+impl TryFrom<Expression> for Polynomial {
+    type Error = Error;
+
+    fn try_from(expression: Expression) -> Result<Self> {
+        Polynomial::try_from(&expression)
+    }
+}
+
+impl From<Polynomial> for Expression {
+    fn from(polynomial: Polynomial) -> Self {
+        polynomial.to_expression()
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+
+        let mut s = String::new();
+        for (i, term) in self.terms.iter().enumerate() {
+            if i == 0 {
+                if term.coefficient.negative {
+                    s.push('-');
+                }
+            } else if term.coefficient.negative {
+                s.push_str(" - ");
+            } else {
+                s.push_str(" + ");
+            }
+
+            // coefficients are written as fractions whatever format they were parsed in
+            let coefficient = term.coefficient.abs().simplify();
+            if coefficient.denominator != 1 {
+                let c = format!("{}/{}", coefficient.numerator, coefficient.denominator);
+                if term.variables.is_empty() {
+                    s.push_str(&c);
+                } else {
+                    s.push_str(&format!("({})", c));
+                }
+            } else if term.variables.is_empty() || coefficient.numerator != 1 {
+                s.push_str(&coefficient.numerator.to_string());
+            }
+            for (v, e) in &term.variables {
+                s.push(*v);
+                if *e > 1 {
+                    s.push_str(&format!("^{}", e));
+                }
+            }
+        }
+        write!(f, "{}", s)
+    }
+}
+
+/// Panics when a coefficient does not fit. Use `checked_add` to get an error instead.
+impl ops::Add<Polynomial> for Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, rhs: Polynomial) -> Self::Output {
+        self.checked_add(&rhs).expect("failed to add polynomials")
+    }
+}
+
+/// Panics when a coefficient does not fit. Use `checked_sub` to get an error instead.
+impl ops::Sub<Polynomial> for Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, rhs: Polynomial) -> Self::Output {
+        self.checked_sub(&rhs).expect("failed to subtract polynomials")
+    }
+}
+
+/// Panics when a coefficient or exponent does not fit. Use `checked_mul` to get an error instead.
+impl ops::Mul<Polynomial> for Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, rhs: Polynomial) -> Self::Output {
+        self.checked_mul(&rhs).expect("failed to multiply polynomials")
+    }
+}
+
+impl ops::Neg for Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Self::Output {
+        Polynomial {
+            terms: self
+                .terms
+                .into_iter()
+                .map(|t| Term {
+                    coefficient: t.coefficient.neg(),
+                    variables: t.variables,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_expression;
+    use crate::polynomial::Polynomial;
+    use crate::rational_number::RationalNumber;
+    use crate::Error;
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    fn polynomial(s: &str) -> Polynomial {
+        Polynomial::try_from(parse_expression(s).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_polynomials() {
+        let p = polynomial("3x^2 + 2x - 5");
+        assert_eq!(p.to_string(), "3x^2 + 2x - 5");
+        assert_eq!(p.degree(), Some(2));
+        assert_eq!(p.leading_coefficient(), RationalNumber::from(3));
+
+        let mut values = HashMap::new();
+        values.insert('x', RationalNumber::from(2));
+        assert_eq!(p.evaluate(&values).unwrap(), RationalNumber::from(11));
+        assert!(p.evaluate(&HashMap::new()).is_err());
+
+        assert_eq!(polynomial("-x^2 + x/2").to_string(), "-x^2 + (1/2)x");
+        assert_eq!(polynomial("x/3 + 2.5x^2").to_string(), "(5/2)x^2 + (1/3)x");
+        assert_eq!(polynomial("0.25 + 1.5x").to_string(), "(3/2)x + 1/4");
+        assert_eq!(polynomial("1/3 x + 1").to_string(), "(1/3)x + 1");
+        assert_eq!(polynomial("2(x - 3) - 2x").to_string(), "-6");
+        assert_eq!(polynomial("x - x").to_string(), "0");
+        assert!(Polynomial::try_from(parse_expression("2^x").unwrap()).is_err());
+        assert!(Polynomial::try_from(parse_expression("1 -: x").unwrap()).is_err());
+        assert!(matches!(Polynomial::try_from(parse_expression("(x + 1)^40").unwrap()), Err(Error::Overflow)));
+        assert!(matches!(Polynomial::try_from(parse_expression("100000x * 100000x").unwrap()), Err(Error::Overflow)));
+
+        let mut values = HashMap::new();
+        values.insert('x', RationalNumber::from(100000));
+        assert!(matches!(polynomial("x^2").evaluate(&values), Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn multiplies_polynomials() {
+        assert_eq!(polynomial("(x + 1)(x - 1)").to_string(), "x^2 - 1");
+        assert_eq!(polynomial("(x + y)^2").to_string(), "x^2 + 2xy + y^2");
+        assert_eq!(polynomial("y^2 + x*y + x^3").degree_in('y'), Some(2));
+        assert_eq!(polynomial("y^2 + x*y + x^3").to_string(), "x^3 + xy + y^2");
+    }
+
+    #[test]
+    fn divides_polynomials() {
+        let (q, r) = polynomial("x^3 - 1").div_rem(&polynomial("x - 1")).unwrap();
+        assert_eq!(q.to_string(), "x^2 + x + 1");
+        assert!(r.is_zero());
+
+        let (q, r) = polynomial("x^2 + 1").div_rem(&polynomial("x - 1")).unwrap();
+        assert_eq!(q.to_string(), "x + 1");
+        assert_eq!(r.to_string(), "2");
+
+        let (q, r) = polynomial("x^2 + 1").div_rem(&polynomial("2x")).unwrap();
+        assert_eq!(q.to_string(), "(1/2)x");
+        assert_eq!(r.to_string(), "1");
+
+        assert!(polynomial("x").div_rem(&Polynomial::zero()).is_err());
+        let divisor = polynomial("x/4294967295 + 1");
+        assert!(matches!(polynomial("4294967295x^2").div_rem(&divisor), Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn deserializes_only_normalized_polynomials() {
+        let p = polynomial("3x^2 + 2x*y - 5");
+        let json = serde_json::to_string(&p).unwrap();
+        assert_eq!(serde_json::from_str::<Polynomial>(&json).unwrap(), p);
+
+
+        let term = |n: u32, variables: &str| {
+            format!(
+                r#"{{"coefficient": {{"numerator": {}, "denominator": 1, "negative": false, "format": "Fraction"}}, "variables": [{}]}}"#,
+                n, variables
+            )
+        };
+        let parse = |terms: &[String]| serde_json::from_str::<Polynomial>(&format!(r#"{{"terms": [{}]}}"#, terms.join(", ")));
+        assert_eq!(parse(&[term(1, r#"["x", 2]"#), term(2, "")]).unwrap().to_string(), "x^2 + 2");
+        assert!(parse(&[term(2, ""), term(1, r#"["x", 2]"#)]).is_err());
+        assert!(parse(&[term(1, r#"["x", 1]"#), term(2, r#"["x", 1]"#)]).is_err());
+        assert!(parse(&[term(0, r#"["x", 1]"#)]).is_err());
+        assert!(parse(&[term(1, r#"["y", 1], ["x", 1]"#)]).is_err());
+        assert!(parse(&[term(1, r#"["x", 0]"#)]).is_err());
+    }
+
+    #[test]
+    fn converts_to_expressions() {
+        let p = polynomial("3x^2 + 2x - 5");
+        let e = p.to_expression();
+        assert_eq!(e.to_string(), "3 * x^2 + 2 * x - 5");
+        assert_eq!(Polynomial::try_from(&e).unwrap(), p);
+        assert_eq!(polynomial("-x*y").to_expression().to_string(), "-x * y");
+        let p = polynomial("-x^2 * y + 3");
+        assert_eq!(p.to_expression().to_string(), "-x^2 * y + 3");
+        assert_eq!(Polynomial::try_from(&p.to_expression()).unwrap(), p);
+        assert_eq!(p.to_expression().substitute('x', 2).substitute('y', 1).evaluate().to_string(), "-1");
+    }
+}
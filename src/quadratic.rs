@@ -0,0 +1,455 @@
+use crate::polynomial::Polynomial;
+use crate::radical::Radical;
+use crate::rational_number::RationalNumber;
+use crate::{Error, Result};
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use ts_rs::TS;
+
+const MAX_FACTOR: i128 = 1 << 21;
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum RootClassification {
+    TwoRational,   // positive perfect square discriminant
+    TwoIrrational, // positive discriminant that is not a perfect square
+    OneRepeated,   // zero discriminant
+    TwoComplex,    // negative discriminant
+}
+
+impl RootClassification {
+    pub fn as_str(&self) -> String {
+        let s = match self {
+            RootClassification::TwoRational => "two rational roots",
+            RootClassification::TwoIrrational => "two irrational roots",
+            RootClassification::OneRepeated => "one repeated rational root",
+            RootClassification::TwoComplex => "two complex roots",
+        };
+        s.to_string()
+    }
+}
+
+/// The roots `rational ± radical`, or `rational ± i·radical` when imaginary. The radical is a
+/// positive square root in simplest form.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConjugateRoots {
+    pub rational: RationalNumber,
+    pub radical: Radical,
+    pub imaginary: bool,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", content = "value")]
+pub enum QuadraticRoots {
+    Rational(Vec<RationalNumber>), // increasing, with a repeated root listed once
+    Conjugate(ConjugateRoots),
+}
+
+impl QuadraticRoots {
+    /// Plain text such as `-3, 1/2` or `(3 ± √5)/2`.
+    pub fn as_str(&self) -> String {
+        match self {
+            QuadraticRoots::Rational(roots) => roots.iter().map(|r| r.as_str(None)).collect::<Vec<String>>().join(", "),
+            QuadraticRoots::Conjugate(roots) => conjugate_str(roots, false),
+        }
+    }
+
+    pub fn as_latex(&self) -> String {
+        match self {
+            QuadraticRoots::Rational(roots) => roots.iter().map(latex_fraction).collect::<Vec<String>>().join(", "),
+            QuadraticRoots::Conjugate(roots) => conjugate_str(roots, true),
+        }
+    }
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuadraticStep {
+    pub description: String,
+    pub result: String,
+}
+
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuadraticSolution {
+    pub discriminant: RationalNumber,
+    pub classification: RootClassification,
+    pub roots: QuadraticRoots,
+    pub steps: Vec<QuadraticStep>, // factoring steps, only when the roots are rational
+}
+
+/// Solves `ax^2 + bx + c = 0` exactly, factoring when the roots are rational.
+pub fn solve_quadratic(a: RationalNumber, b: RationalNumber, c: RationalNumber) -> Result<QuadraticSolution> {
+    solve(a, b, c, 'x')
+}
+
+impl Polynomial {
+    /// Solves a quadratic in one variable set equal to zero.
+    pub fn solve_quadratic(&self) -> Result<QuadraticSolution> {
+        let variable = match self.variables().as_slice() {
+            [v] if self.degree() == Some(2) => *v,
+            _ => return Err(Error::NotQuadratic),
+        };
+        let coefficient = |exp: u32| {
+            self.terms()
+                .iter()
+                .find(|t| t.exponent(variable) == exp)
+                .map(|t| t.coefficient)
+                .unwrap_or_else(|| RationalNumber::from(0))
+        };
+        solve(coefficient(2), coefficient(1), coefficient(0), variable)
+    }
+}
+
+fn solve(a: RationalNumber, b: RationalNumber, c: RationalNumber, variable: char) -> Result<QuadraticSolution> {
+    if a.numerator == 0 {
+        return Err(Error::NotQuadratic);
+    } else if [a, b, c].iter().any(|n| n.denominator == 0) {
+        return Err(Error::DenominatorCannotBeZero);
+    }
+
+    // scale to integer coefficients with no common factor and a positive leading coefficient
+    let l = crate::lcm_u128(crate::lcm_u128(a.denominator as u128, b.denominator as u128), c.denominator as u128) as i128;
+    let scale = |n: &RationalNumber| mul(n.signed_numerator() as i128, l / n.denominator as i128);
+    let (mut qa, mut qb, mut qc) = (scale(&a)?, scale(&b)?, scale(&c)?);
+    let mut g = gcd(gcd(qa, qb), qc);
+    if qa < 0 {
+        g = -g;
+    }
+    qa /= g;
+    qb /= g;
+    qc /= g;
+
+    let d = mul(qb, qb)?.checked_sub(mul(4, mul(qa, qc)?)?).ok_or(Error::Overflow)?;
+    // the discriminant of the original coefficients is d * (g / l)^2
+    let discriminant = fraction(mul(d, mul(g, g)?)?, mul(l, l)?)?;
+
+    let (k, r) = square_free(d.abs())?;
+    let two_a = mul(2, qa)?;
+    let (classification, roots) = if d == 0 {
+        (RootClassification::OneRepeated, QuadraticRoots::Rational(vec![fraction(-qb, two_a)?]))
+    } else if d > 0 && r == 1 {
+        let mut roots = vec![fraction(-qb - k, two_a)?, fraction(-qb + k, two_a)?];
+        roots.sort();
+        (RootClassification::TwoRational, QuadraticRoots::Rational(roots))
+    } else {
+        let roots = ConjugateRoots {
+            rational: fraction(-qb, two_a)?,
+            radical: Radical::new(fraction(k, two_a)?, r, 2)?,
+            imaginary: d < 0,
+        };
+        let classification = if d < 0 {
+            RootClassification::TwoComplex
+        } else {
+            RootClassification::TwoIrrational
+        };
+        (classification, QuadraticRoots::Conjugate(roots))
+    };
+
+    let steps = match &roots {
+        QuadraticRoots::Rational(_) => {
+            let scale = fraction(l, g)?;
+            factoring_steps(qa, qb, qc, k, &scale, variable)?
+        }
+        QuadraticRoots::Conjugate(_) => Vec::new(),
+    };
+
+    Ok(QuadraticSolution {
+        discriminant,
+        classification,
+        roots,
+        steps,
+    })
+}
+
+// factors qa x^2 + qb x + qc, which has rational roots, by grouping
+fn factoring_steps(qa: i128, qb: i128, qc: i128, sqrt_d: i128, scale: &RationalNumber, v: char) -> Result<Vec<QuadraticStep>> {
+    let mut steps = Vec::new();
+    let mut step = |description: String, result: String| steps.push(QuadraticStep { description, result });
+
+    if *scale != RationalNumber::from(1) {
+        let description = if scale.numerator == 1 && !scale.negative {
+            format!("divide both sides by {}", scale.denominator)
+        } else {
+            format!("multiply both sides by {}", scale)
+        };
+        step(description, format!("{} = 0", terms_str(&[(qa, 2), (qb, 1), (qc, 0)], v)));
+    }
+
+    // the roots are -m/qa and -n/qa, with m + n = qb and mn = qa * qc
+    let (m, n) = ((qb - sqrt_d) / 2, (qb + sqrt_d) / 2);
+    let (r1, r2) = (fraction(-m, qa)?, fraction(-n, qa)?);
+    let solutions = if r1 == r2 {
+        format!("{} = {}", v, r1)
+    } else {
+        let (low, high) = if r1 < r2 { (r1, r2) } else { (r2, r1) };
+        format!("{} = {} or {} = {}", v, low, v, high)
+    };
+
+    if qc == 0 {
+        step(
+            format!("factor out {}", v),
+            format!("{}({}) = 0", v, terms_str(&[(qa, 1), (qb, 0)], v)),
+        );
+        step("set each factor equal to zero".to_string(), solutions);
+        return Ok(steps);
+    }
+
+    // put the factor with a nonzero split term first so grouping never divides by zero
+    let (m, n) = if m == 0 { (n, m) } else { (m, n) };
+    if qa == 1 {
+        step(
+            format!("find two numbers with product {} and sum {}", qc, qb),
+            format!("{} and {}", m, n),
+        );
+    } else {
+        step(
+            format!("find two numbers with product ac = {} and sum b = {}", mul(qa, qc)?, qb),
+            format!("{} and {}", m, n),
+        );
+        step(
+            "split the middle term".to_string(),
+            format!("{} = 0", terms_str(&[(qa, 2), (m, 1), (n, 1), (qc, 0)], v)),
+        );
+        let g1 = gcd(qa, m);
+        let g2 = mul(n, g1)? / qa;
+        let binomial = terms_str(&[(qa / g1, 1), (m / g1, 0)], v);
+        let first = if g1 == 1 { v.to_string() } else { format!("{}{}", g1, v) };
+        let sign = if g2 < 0 { "-" } else { "+" };
+        step(
+            "factor by grouping".to_string(),
+            format!("{}({}) {} {}({}) = 0", first, binomial, sign, g2.abs(), binomial),
+        );
+    }
+
+    let g1 = gcd(qa, m);
+    let first = terms_str(&[(qa / g1, 1), (m / g1, 0)], v);
+    let second = terms_str(&[(g1, 1), (mul(n, g1)? / qa, 0)], v);
+    let factored = if first == second {
+        format!("({})^2", first)
+    } else {
+        format!("({})({})", second, first)
+    };
+    step("factor".to_string(), format!("{} = 0", factored));
+    step("set each factor equal to zero".to_string(), solutions);
+    Ok(steps)
+}
+
+// `3x^2 - x + 5`, skipping zero terms and writing the coefficient 1 only on constants
+fn terms_str(terms: &[(i128, u32)], v: char) -> String {
+    let mut s = String::new();
+    for (coefficient, exp) in terms.iter().filter(|(c, _)| *c != 0) {
+        if s.is_empty() {
+            if *coefficient < 0 {
+                s.push('-');
+            }
+        } else if *coefficient < 0 {
+            s.push_str(" - ");
+        } else {
+            s.push_str(" + ");
+        }
+        let c = coefficient.abs();
+        if c != 1 || *exp == 0 {
+            s.push_str(&c.to_string());
+        }
+        match exp {
+            0 => {}
+            1 => s.push(v),
+            _ => s.push_str(&format!("{}^{}", v, exp)),
+        }
+    }
+    if s.is_empty() {
+        s.push('0');
+    }
+    s
+}
+
+fn conjugate_str(roots: &ConjugateRoots, latex: bool) -> String {
+    let (p, q) = (roots.rational, roots.radical.coefficient());
+    let denominator = crate::lcm(p.denominator, q.denominator) as i64;
+    let a = p.signed_numerator() * (denominator / p.denominator as i64);
+    let b = q.numerator as i64 * (denominator / q.denominator as i64);
+
+    let root = match (latex, roots.radical.radicand()) {
+        (_, 1) => String::new(),
+        (true, r) => format!("\\sqrt{{{}}}", r),
+        (false, r) => format!("√{}", r),
+    };
+    let imaginary = if roots.imaginary { "i" } else { "" };
+    let coefficient = if b == 1 && !(root.is_empty() && imaginary.is_empty()) {
+        String::new()
+    } else {
+        b.to_string()
+    };
+    let radical = format!("{}{}{}", coefficient, imaginary, root);
+    let pm = if latex { "\\pm" } else { "±" };
+
+    match (a, denominator, latex) {
+        (0, 1, _) => format!("{}{}", pm, radical),
+        (_, 1, _) => format!("{} {} {}", a, pm, radical),
+        (0, d, true) => format!("{}\\frac{{{}}}{{{}}}", pm, radical, d),
+        (0, d, false) => format!("{}{}/{}", pm, radical, d),
+        (a, d, true) => format!("\\frac{{{} {} {}}}{{{}}}", a, pm, radical, d),
+        (a, d, false) => format!("({} {} {})/{}", a, pm, radical, d),
+    }
+}
+
+fn latex_fraction(n: &RationalNumber) -> String {
+    let sign = if n.negative { "-" } else { "" };
+    if n.denominator == 1 {
+        format!("{}{}", sign, n.numerator)
+    } else {
+        format!("{}\\frac{{{}}}{{{}}}", sign, n.numerator, n.denominator)
+    }
+}
+
+// the largest k with n = k^2 * r, and r, which has to fit a radicand. Once every factor up to
+// the cube root of what is left has been divided out, the rest is 1, a prime, a prime squared or a
+// product of two primes. Trial division stops at MAX_FACTOR, so a larger rest that is not a square
+// is only known to fit when the search finished.
+fn square_free(n: i128) -> Result<(i128, u32)> {
+    if n == 0 {
+        return Ok((0, 1));
+    }
+    let (mut k, mut r, mut rest) = (1, 1, n);
+    let mut f = 2;
+    while f * f * f <= rest && f <= MAX_FACTOR {
+        while rest % (f * f) == 0 {
+            rest /= f * f;
+            k *= f;
+        }
+        if rest % f == 0 {
+            rest /= f;
+            r *= f;
+            if r > u32::MAX as i128 {
+                return Err(Error::Overflow);
+            }
+        }
+        f += 1;
+    }
+    let root = rest.isqrt();
+    if root * root == rest {
+        k *= root;
+    } else if f * f * f > rest {
+        r *= rest;
+    } else {
+        return Err(Error::Overflow);
+    }
+    Ok((k, u32::try_from(r).map_err(|_| Error::Overflow)?))
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    crate::gcf_u128(a.unsigned_abs(), b.unsigned_abs()) as i128
+}
+
+fn mul(a: i128, b: i128) -> Result<i128> {
+    a.checked_mul(b).ok_or(Error::Overflow)
+}
+
+// n/d in lowest terms
+fn fraction(n: i128, d: i128) -> Result<RationalNumber> {
+    let g = gcd(n, d).max(1) * d.signum();
+    RationalNumber::from_parts(n / g, d / g)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_expression;
+    use crate::polynomial::Polynomial;
+    use crate::quadratic::{solve_quadratic, QuadraticRoots, QuadraticSolution, RootClassification};
+    use crate::radical::Radical;
+    use crate::rational_number::RationalNumber;
+    use crate::Error;
+    use std::convert::TryFrom;
+
+    fn solve(s: &str) -> QuadraticSolution {
+        Polynomial::try_from(parse_expression(s).unwrap()).unwrap().solve_quadratic().unwrap()
+    }
+
+    #[test]
+    fn solves_rational_roots() {
+        let solution = solve("2x^2 + 5x - 3");
+        assert_eq!(solution.discriminant, RationalNumber::from(49));
+        assert_eq!(solution.classification, RootClassification::TwoRational);
+        assert_eq!(solution.roots.as_str(), "-3, 1/2");
+        assert_eq!(solution.roots.as_latex(), "-3, \\frac{1}{2}");
+        let steps: Vec<String> = solution.steps.iter().map(|s| format!("{}: {}", s.description, s.result)).collect();
+        assert_eq!(
+            steps,
+            vec![
+                "find two numbers with product ac = -6 and sum b = 5: -1 and 6",
+                "split the middle term: 2x^2 - x + 6x - 3 = 0",
+                "factor by grouping: x(2x - 1) + 3(2x - 1) = 0",
+                "factor: (x + 3)(2x - 1) = 0",
+                "set each factor equal to zero: x = -3 or x = 1/2",
+            ]
+        );
+
+        let solution = solve("1/2 y^2 - 2");
+        assert_eq!(solution.roots.as_str(), "-2, 2");
+        assert_eq!(solution.steps[0].description, "multiply both sides by 2");
+        assert_eq!(solution.steps[0].result, "y^2 - 4 = 0");
+
+        let solution = solve("x^2 + 6x + 9");
+        assert_eq!(solution.classification, RootClassification::OneRepeated);
+        assert_eq!(solution.steps.last().unwrap().result, "x = -3");
+        assert_eq!(solution.steps[1].result, "(x + 3)^2 = 0");
+
+        assert_eq!(solve("3x^2 - 6x").steps[1].result, "x(x - 2) = 0");
+    }
+
+    #[test]
+    fn solves_irrational_roots() {
+        let solution = solve("x^2 - 3x + 1");
+        assert_eq!(solution.classification, RootClassification::TwoIrrational);
+        assert_eq!(solution.roots.as_str(), "(3 ± √5)/2");
+        assert_eq!(solution.roots.as_latex(), "\\frac{3 \\pm \\sqrt{5}}{2}");
+        assert!(solution.steps.is_empty());
+
+        assert_eq!(solve("x^2 - 8").roots.as_str(), "±2√2");
+        assert_eq!(solve("x^2 + 2x - 1").roots.as_str(), "-1 ± √2");
+        assert_eq!(solve("4x^2 - 3").roots.as_latex(), "\\pm\\frac{\\sqrt{3}}{2}");
+
+        let solution = solve("x^2 - x + 1");
+        assert_eq!(solution.classification, RootClassification::TwoComplex);
+        assert_eq!(solution.discriminant, RationalNumber::from(-3));
+        assert_eq!(solution.roots.as_str(), "(1 ± i√3)/2");
+        assert_eq!(solve("x^2 + 4").roots.as_str(), "±2i");
+
+        assert!(solve_quadratic(RationalNumber::from(0), RationalNumber::from(1), RationalNumber::from(1)).is_err());
+
+        let roots = match solve("x^2 - 3x + 1").roots {
+            QuadraticRoots::Conjugate(roots) => roots,
+            roots => panic!("expected conjugate roots, got {:?}", roots),
+        };
+        assert_eq!(roots.radical, Radical::new(RationalNumber::parse("1/2").unwrap(), 5, 2).unwrap());
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let n = |s: &str| RationalNumber::parse(s).unwrap();
+        let result = solve_quadratic(n("1/4294967295"), n("1/4294967294"), n("4294967295"));
+        assert!(matches!(result, Err(Error::Overflow)));
+        let result = solve_quadratic(n("1"), n("-4294967295"), n("4294967294"));
+        assert!(matches!(result, Err(Error::Overflow)));
+
+        // the square-free part of 4 * 1073741789 * 4294967291 does not fit a radicand
+        let result = solve_quadratic(n("1/4294967291"), n("0"), n("-1073741789"));
+        assert!(matches!(result, Err(Error::Overflow)));
+
+        // 4294235138 is 2 * 46337^2, and the square of the prime 46337 still leaves the radical
+        let roots = match solve_quadratic(n("1/4"), n("0"), n("-4294235138")).unwrap().roots {
+            QuadraticRoots::Conjugate(roots) => roots,
+            roots => panic!("expected conjugate roots, got {:?}", roots),
+        };
+        assert_eq!(roots.radical, Radical::new(n("92674"), 2, 2).unwrap());
+    }
+}
@@ -0,0 +1,473 @@
+use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+use crate::{Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::convert::TryFrom;
+use std::{fmt, ops};
+use ts_rs::TS;
+
+/// `coefficient` times the `index`th root of `radicand`, kept in simplest radical form: the
+/// radicand has no perfect `index`th power factors and the index is as small as possible.
+#[derive(TS)]
+#[ts(export, export_to = "../bindings/")]
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct Radical {
+    coefficient: RationalNumber,
+    radicand: u32,
+    index: u32,
+}
+
+// deserialized fields, simplified by `Radical::new`
+#[derive(Deserialize)]
+struct RadicalFields {
+    coefficient: RationalNumber,
+    radicand: u32,
+    index: u32,
+}
+
+impl<'de> Deserialize<'de> for Radical {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let f = RadicalFields::deserialize(deserializer)?;
+        Radical::new(f.coefficient, f.radicand, f.index).map_err(de::Error::custom)
+    }
+}
+
+impl Radical {
+    pub fn new(coefficient: RationalNumber, radicand: u32, index: u32) -> Result<Self> {
+        if index == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        }
+        Radical {
+            coefficient,
+            radicand,
+            index,
+        }
+        .simplify()
+    }
+
+    pub fn sqrt(n: RationalNumber) -> Result<Self> {
+        Radical::root(n, 2)
+    }
+
+    /// The `index`th root of `n`, moving any denominator out of the radicand so `√(1/2)` becomes
+    /// `(1/2)√2`. Even roots of negative numbers are not real.
+    pub fn root(n: RationalNumber, index: u32) -> Result<Self> {
+        if index == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        }
+        let n = n.simplify();
+        if n.negative && n.numerator != 0 && index.is_multiple_of(2) {
+            return Err(Error::NotReal);
+        }
+        let radicand = n
+            .denominator
+            .checked_pow(index - 1)
+            .and_then(|d| d.checked_mul(n.numerator))
+            .ok_or(Error::Overflow)?;
+        let coefficient = RationalNumber::new(1, n.denominator, n.negative, NumberDisplayFormat::Fraction);
+        Radical::new(coefficient, radicand, index)
+    }
+
+    pub fn coefficient(&self) -> RationalNumber {
+        self.coefficient
+    }
+
+    pub fn radicand(&self) -> u32 {
+        self.radicand
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn is_rational(&self) -> bool {
+        self.radicand == 1
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        let c = self.coefficient.numerator as f64 / self.coefficient.denominator as f64;
+        let c = if self.coefficient.negative { -c } else { c };
+        c * (self.radicand as f64).powf(1.0 / self.index as f64)
+    }
+
+    pub fn neg(&self) -> Radical {
+        Radical {
+            coefficient: self.coefficient.neg(),
+            ..*self
+        }
+    }
+
+    /// Rationalizes the denominator, so `1/√2` is `(1/2)√2`.
+    pub fn reciprocal(&self) -> Result<Radical> {
+        if self.coefficient.numerator == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        }
+        let radicand = self.radicand.checked_pow(self.index - 1).ok_or(Error::Overflow)?;
+        let coefficient = self.coefficient.checked_mul(&RationalNumber::from(self.radicand))?.reciprocal();
+        Radical::new(coefficient, radicand, self.index)
+    }
+
+    /// Multiplies radicals of any index by writing both with their least common index, so
+    /// `∛2 · √2` is `⁶√32`. Fails with `Overflow` when the product does not fit.
+    pub fn checked_mul(&self, rhs: &Radical) -> Result<Radical> {
+        let index = u32::try_from(crate::lcm_u128(self.index as u128, rhs.index as u128)).map_err(|_| Error::Overflow)?;
+        let radicand = self
+            .radicand
+            .checked_pow(index / self.index)
+            .and_then(|a| rhs.radicand.checked_pow(index / rhs.index).and_then(|b| a.checked_mul(b)))
+            .ok_or(Error::Overflow)?;
+        Radical::new(self.coefficient.checked_mul(&rhs.coefficient)?, radicand, index)
+    }
+
+    pub fn checked_div(&self, rhs: &Radical) -> Result<Radical> {
+        self.checked_mul(&rhs.reciprocal()?)
+    }
+
+    /// Adds like radicals, which share a radicand and index. Rational numbers are like each other.
+    pub fn try_add(&self, other: &Radical) -> Result<Radical> {
+        if self.coefficient.numerator == 0 {
+            Ok(*other)
+        } else if other.coefficient.numerator == 0 {
+            Ok(*self)
+        } else if self.radicand == other.radicand && self.index == other.index {
+            Radical::new(self.coefficient + other.coefficient, self.radicand, self.index)
+        } else {
+            Err(Error::UnlikeRadicals)
+        }
+    }
+
+    pub fn try_sub(&self, other: &Radical) -> Result<Radical> {
+        self.try_add(&other.neg())
+    }
+
+    pub fn as_latex(&self) -> String {
+        if self.radicand == 1 {
+            return latex_number(&self.coefficient);
+        }
+        let root = match self.index {
+            2 => format!("\\sqrt{{{}}}", self.radicand),
+            n => format!("\\sqrt[{}]{{{}}}", n, self.radicand),
+        };
+        match self.coefficient {
+            c if c == RationalNumber::from(1) => root,
+            c if c == RationalNumber::from(-1) => format!("-{}", root),
+            c => format!("{}{}", latex_number(&c), root),
+        }
+    }
+
+    fn simplify(mut self) -> Result<Self> {
+        if self.radicand == 0 || self.coefficient.numerator == 0 {
+            return Ok(Radical {
+                coefficient: RationalNumber::from(0),
+                radicand: 1,
+                index: 2,
+            });
+        }
+        if self.index == 1 {
+            self.coefficient = self.coefficient.checked_mul(&RationalNumber::from(self.radicand))?;
+            self.radicand = 1;
+        }
+
+        // each prime's exponent splits into whole powers outside the root and a remainder inside,
+        // and exponents left inside that share a factor with the index lower it, so ⁴√4 is √2
+        let factors = prime_factors(self.radicand);
+        let lower = factors
+            .iter()
+            .map(|(_, e)| e % self.index)
+            .filter(|e| *e != 0)
+            .fold(self.index, crate::gcf);
+        let (mut outside, mut inside) = (1u32, 1u32);
+        for (p, e) in factors {
+            outside *= p.pow(e / self.index);
+            inside *= p.pow(e % self.index / lower);
+        }
+        self.coefficient = self.coefficient.checked_mul(&RationalNumber::from(outside))?;
+        self.radicand = inside;
+        self.index /= lower;
+        if self.radicand == 1 {
+            self.index = 2;
+        }
+        let c = self.coefficient.simplify();
+        self.coefficient = if c.denominator == 1 {
+            c.set_display_format(NumberDisplayFormat::Decimal(None))
+        } else {
+            c.set_display_format(NumberDisplayFormat::Fraction)
+        };
+        Ok(self)
+    }
+
+    // |self|^exp as a fraction, when it fits
+    fn pow_parts(&self, exp: u32) -> Option<(u128, u128)> {
+        let n = (self.coefficient.numerator as u128).checked_pow(exp)?;
+        let d = (self.coefficient.denominator as u128).checked_pow(exp)?;
+        let r = (self.radicand as u128).checked_pow(exp / self.index)?;
+        Some((n.checked_mul(r)?, d))
+    }
+}
+
+// prime factors with their exponents, by trial division up to the square root
+fn prime_factors(mut n: u32) -> Vec<(u32, u32)> {
+    let mut factors = Vec::new();
+    let mut p = 2u32;
+    while p as u64 * p as u64 <= n as u64 {
+        let mut e = 0;
+        while n.is_multiple_of(p) {
+            n /= p;
+            e += 1;
+        }
+        if e > 0 {
+            factors.push((p, e));
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+fn latex_number(n: &RationalNumber) -> String {
+    if n.denominator == 1 {
+        n.as_str(None)
+    } else {
+        let sign = if n.negative { "-" } else { "" };
+        format!("{}\\frac{{{}}}{{{}}}", sign, n.numerator, n.denominator)
+    }
+}
+
+impl PartialEq for Radical {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Radical {}
+
+// simplest radical form is unique, so equal radicals have equal fields
+impl Hash for Radical {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.coefficient.hash(state);
+        self.radicand.hash(state);
+        self.index.hash(state);
+    }
+}
+
+impl PartialOrd for Radical {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Radical {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |r: &Radical| match (r.coefficient.numerator, r.coefficient.negative) {
+            (0, _) => 0,
+            (_, true) => -1,
+            (_, false) => 1,
+        };
+        let (a, b) = (sign(self), sign(other));
+        if a != b || a == 0 {
+            return a.cmp(&b);
+        }
+
+        // compare magnitudes raised to a power that clears both roots
+        let exp = crate::lcm(self.index, other.index);
+        let magnitude = match (self.pow_parts(exp), other.pow_parts(exp)) {
+            (Some((n1, d1)), Some((n2, d2))) => match (n1.checked_mul(d2), n2.checked_mul(d1)) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                _ => self.as_f64().abs().total_cmp(&other.as_f64().abs()),
+            },
+            _ => self.as_f64().abs().total_cmp(&other.as_f64().abs()),
+        };
+        if a < 0 {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl PartialEq<RationalNumber> for Radical {
+    fn eq(&self, other: &RationalNumber) -> bool {
+        *self == Radical::from(*other)
+    }
+}
+
+impl PartialOrd<RationalNumber> for Radical {
+    fn partial_cmp(&self, other: &RationalNumber) -> Option<Ordering> {
+        Some(self.cmp(&Radical::from(*other)))
+    }
+}
+
+impl From<RationalNumber> for Radical {
+    fn from(n: RationalNumber) -> Self {
+        Radical {
+            coefficient: n.simplify(),
+            radicand: 1,
+            index: 2,
+        }
+    }
+}
+
+impl TryFrom<Radical> for RationalNumber {
+    type Error = Error;
+
+    fn try_from(r: Radical) -> Result<Self> {
+        if r.is_rational() {
+            Ok(r.coefficient)
+        } else {
+            Err(Error::NotRational)
+        }
+    }
+}
+
+impl fmt::Display for Radical {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.radicand == 1 {
+            return write!(f, "{}", self.coefficient);
+        }
+        let c = self.coefficient;
+        let coefficient = if c == RationalNumber::from(1) {
+            String::new()
+        } else if c == RationalNumber::from(-1) {
+            "-".to_string()
+        } else if c.denominator == 1 {
+            c.as_str(None)
+        } else {
+            format!("({})", c)
+        };
+        let root = match self.index {
+            2 => "√".to_string(),
+            3 => "∛".to_string(),
+            4 => "∜".to_string(),
+            n => {
+                let superscript = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+                let digits: String = n.to_string().chars().map(|d| superscript[d as usize - '0' as usize]).collect();
+                format!("{}√", digits)
+            }
+        };
+        write!(f, "{}{}{}", coefficient, root, self.radicand)
+    }
+}
+
+/// Panics when the product does not fit. Use `checked_mul` to get an error instead.
+impl ops::Mul<Radical> for Radical {
+    type Output = Radical;
+
+    fn mul(self, rhs: Radical) -> Self::Output {
+        self.checked_mul(&rhs).expect("failed to multiply radicals")
+    }
+}
+
+impl ops::Mul<RationalNumber> for Radical {
+    type Output = Radical;
+
+    fn mul(self, rhs: RationalNumber) -> Self::Output {
+        self * Radical::from(rhs)
+    }
+}
+
+impl ops::Mul<Radical> for RationalNumber {
+    type Output = Radical;
+
+    fn mul(self, rhs: Radical) -> Self::Output {
+        Radical::from(self) * rhs
+    }
+}
+
+/// Panics when dividing by zero or when the quotient does not fit. Use `checked_div` to get an
+/// error instead.
+impl ops::Div<Radical> for Radical {
+    type Output = Radical;
+
+    fn div(self, rhs: Radical) -> Self::Output {
+        self.checked_div(&rhs).expect("failed to divide radicals")
+    }
+}
+
+impl ops::Div<RationalNumber> for Radical {
+    type Output = Radical;
+
+    fn div(self, rhs: RationalNumber) -> Self::Output {
+        self / Radical::from(rhs)
+    }
+}
+
+impl ops::Div<Radical> for RationalNumber {
+    type Output = Radical;
+
+    fn div(self, rhs: Radical) -> Self::Output {
+        Radical::from(self) / rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::radical::Radical;
+    use crate::rational_number::RationalNumber;
+    use crate::Error;
+
+    fn sqrt(s: &str) -> Radical {
+        Radical::sqrt(RationalNumber::parse(s).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn simplifies_radicals() {
+        assert_eq!(sqrt("72").to_string(), "6√2");
+        assert_eq!(sqrt("20/9").to_string(), "(2/3)√5");
+        assert_eq!(sqrt("49").to_string(), "7");
+        assert_eq!(sqrt("1/2").to_string(), "(1/2)√2");
+        assert_eq!(Radical::root(RationalNumber::from(-54), 3).unwrap().to_string(), "-3∛2");
+        assert_eq!(Radical::root(RationalNumber::from(4), 4).unwrap().to_string(), "√2");
+        assert_eq!(Radical::root(RationalNumber::from(64), 6).unwrap().to_string(), "2");
+        assert_eq!(Radical::root(RationalNumber::from(3), 5).unwrap().to_string(), "⁵√3");
+        assert_eq!(sqrt("20/9").as_latex(), "\\frac{2}{3}\\sqrt{5}");
+        assert!(Radical::sqrt(RationalNumber::from(-4)).is_err());
+        assert_eq!(sqrt("4294836225").to_string(), "65535");
+        assert_eq!(sqrt("4294967295").to_string(), "√4294967295");
+        assert_eq!(sqrt("4294967292").to_string(), "6√119304647");
+        assert_eq!(Radical::root(RationalNumber::from(36), 4).unwrap().to_string(), "√6");
+        assert_eq!(Radical::root(RationalNumber::from(2 * 2 * 2 * 9), 6).unwrap().to_string(), "⁶√72");
+    }
+
+    #[test]
+    fn deserializes_in_simplest_form() {
+        let json = serde_json::to_string(&sqrt("2")).unwrap();
+        assert_eq!(serde_json::from_str::<Radical>(&json).unwrap(), sqrt("2"));
+
+        let unsimplified = json.replace(r#""radicand":2"#, r#""radicand":8"#);
+        assert_ne!(unsimplified, json);
+        assert_eq!(serde_json::from_str::<Radical>(&unsimplified).unwrap(), sqrt("8"));
+
+        let zero_index = json.replace(r#""index":2"#, r#""index":0"#);
+        assert_ne!(zero_index, json);
+        assert!(serde_json::from_str::<Radical>(&zero_index).is_err());
+    }
+
+    #[test]
+    fn calculates_with_radicals() {
+        assert_eq!((sqrt("6") * sqrt("3")).to_string(), "3√2");
+        assert_eq!((sqrt("10") / sqrt("5")).to_string(), "√2");
+        assert_eq!((RationalNumber::from(3) / sqrt("3")).to_string(), "√3");
+        assert_eq!(sqrt("2").reciprocal().unwrap().to_string(), "(1/2)√2");
+        assert_eq!(sqrt("8").try_add(&sqrt("18")).unwrap().to_string(), "5√2");
+        assert_eq!(sqrt("8").try_sub(&sqrt("8")).unwrap().to_string(), "0");
+        assert!(sqrt("2").try_add(&sqrt("3")).is_err());
+        assert_eq!((Radical::root(RationalNumber::from(2), 3).unwrap() * sqrt("2")).to_string(), "⁶√32");
+        assert!(matches!(sqrt("65537").checked_mul(&sqrt("65539")), Err(Error::Overflow)));
+        assert!(matches!(sqrt("2").checked_div(&sqrt("0")), Err(Error::DenominatorCannotBeZero)));
+        assert_eq!(sqrt("10").checked_div(&sqrt("5")).unwrap().to_string(), "√2");
+    }
+
+    #[test]
+    fn compares_radicals() {
+        assert!(sqrt("50") > RationalNumber::from(7));
+        assert!(sqrt("50") < RationalNumber::parse("7.1").unwrap());
+        assert!(sqrt("2").neg() < sqrt("3").neg().neg());
+        assert!(sqrt("3").neg() < sqrt("2").neg());
+        assert_eq!(sqrt("16"), RationalNumber::from(4));
+        assert_eq!(Radical::root(RationalNumber::from(4), 4).unwrap(), sqrt("2"));
+        assert!(Radical::root(RationalNumber::from(3), 3).unwrap() > sqrt("2"));
+    }
+}
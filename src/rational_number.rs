@@ -1,6 +1,8 @@
 use crate::{Result, Error, PlaceValue};
 use regex::Regex;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
 use std::ops::{Add, Neg, Range, RangeInclusive};
 use std::str::FromStr;
 use std::{fmt, ops};
@@ -21,6 +23,7 @@ pub enum NumberDisplayFormat {
     Mixed,
 }
 
+#[derive(Debug, Clone)]
 pub enum RangeWrapper<T> {
     Range(Range<T>),
     RangeInclusive(RangeInclusive<T>),
@@ -40,7 +43,7 @@ impl <T> From<RangeInclusive<T>> for RangeWrapper<T> {
 
 #[derive(TS)]
 #[ts(export, export_to = "../bindings/")]
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, Ord, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct RationalNumber {
     pub numerator: u32,
@@ -59,7 +62,7 @@ impl RationalNumber {
         };
         use rand::prelude::SliceRandom;
         let d = *denominators.choose(rng).unwrap();
-        let n = if inclusive && w == end {
+        let n = if (inclusive && w == end) || d == 1 {
             w * d
         } else {
             rng.gen_range(1..d) + (w * d)
@@ -85,7 +88,7 @@ impl RationalNumber {
     }
 
     pub fn as_i32(&self) -> Result<i32> {
-        if self.numerator % self.denominator != 0 {
+        if !self.numerator.is_multiple_of(self.denominator) {
             Err(Error::ParseRationalExpression)
         } else {
             let mut n = (self.numerator / self.denominator) as i32;
@@ -195,14 +198,17 @@ impl RationalNumber {
             u32::from_str(remainder_str)?
         };
 
-        let denominator = 10u32.pow(remainder_str.len() as u32);
+        let denominator = 10u32.checked_pow(remainder_str.len() as u32).ok_or(Error::Overflow)?;
 
         let f = crate::gcf(remainder, denominator);
 
         let remainder = remainder / f;
         let denominator = denominator / f;
 
-        let numerator = denominator * whole + remainder;
+        let numerator = denominator
+            .checked_mul(whole)
+            .and_then(|n| n.checked_add(remainder))
+            .ok_or(Error::Overflow)?;
 
         Ok(RationalNumber {
             numerator,
@@ -212,10 +218,153 @@ impl RationalNumber {
         })
     }
 
+    /// Finds the fraction closest to `f` whose denominator does not exceed `max_denominator`,
+    /// walking the continued fraction expansion of the exact binary value of `f`.
+    pub fn approximate(f: f64, max_denominator: u32) -> Result<Self> {
+        if !f.is_finite() {
+            return Err(Error::NotFinite);
+        } else if max_denominator == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        } else if f.abs() > u32::MAX as f64 {
+            return Err(Error::Overflow);
+        }
+
+        let (mut n, mut d) = exact_parts(f);
+        let exact_denominator = d;
+        let max_denominator = max_denominator as u128;
+        let (mut p0, mut q0, mut p1, mut q1) = (0u128, 1u128, 1u128, 0u128);
+        while d != 0 {
+            let a = n / d;
+            let q2 = q0 + a * q1;
+            if q2 > max_denominator {
+                break;
+            }
+            let p2 = p0 + a * p1;
+            p0 = p1;
+            q0 = q1;
+            p1 = p2;
+            q1 = q2;
+            let r = n - a * d;
+            n = d;
+            d = r;
+        }
+
+        let (numerator, denominator) = if d == 0 {
+            (p1, q1)
+        } else {
+            // choose between the last convergent and the best semiconvergent below the bound
+            let k = (max_denominator - q0) / q1;
+            if 2 * d * (q0 + k * q1) <= exact_denominator {
+                (p1, q1)
+            } else {
+                (p0 + k * p1, q0 + k * q1)
+            }
+        };
+
+        let format = if denominator == 1 {
+            NumberDisplayFormat::Decimal(None)
+        } else {
+            NumberDisplayFormat::Fraction
+        };
+
+        Ok(RationalNumber {
+            numerator: u32::try_from(numerator).map_err(|_| Error::Overflow)?,
+            denominator: u32::try_from(denominator).map_err(|_| Error::Overflow)?,
+            negative: f < 0.0 && numerator != 0,
+            format,
+        })
+    }
+
+    /// Recovers the exact binary value of `f`, which fails unless both the numerator and the
+    /// power of two denominator fit in a `u32`.
+    pub fn from_f64_exact(f: f64) -> Result<Self> {
+        if !f.is_finite() {
+            return Err(Error::NotFinite);
+        }
+
+        let (mantissa, exponent) = f64_parts(f);
+        let (numerator, denominator) = if mantissa == 0 {
+            (0, 1)
+        } else if exponent >= 0 {
+            let n = u32::try_from(mantissa).map_err(|_| Error::Overflow)?;
+            (n.checked_mul(2u32.checked_pow(exponent as u32).ok_or(Error::Overflow)?).ok_or(Error::Overflow)?, 1)
+        } else {
+            let n = u32::try_from(mantissa).map_err(|_| Error::Overflow)?;
+            (n, 2u32.checked_pow(-exponent as u32).ok_or(Error::Overflow)?)
+        };
+
+        Ok(RationalNumber {
+            numerator,
+            denominator,
+            negative: f < 0.0 && numerator != 0,
+            format: NumberDisplayFormat::Decimal(None),
+        })
+    }
+
     pub fn pow(&self, exp: &RationalNumber) -> RationalNumber {
         self.as_f32().powf(exp.as_f32()).into()
     }
 
+    /// The same sum as `+`, but `Overflow` instead of a panic when it does not fit.
+    pub fn checked_add(&self, rhs: &RationalNumber) -> Result<RationalNumber> {
+        if self.denominator == 0 || rhs.denominator == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        }
+        let denominator = (self.denominator / crate::gcf(self.denominator, rhs.denominator)) as u64 * rhs.denominator as u64;
+        let scaled = |n: &RationalNumber| n.signed_numerator() as i128 * (denominator / n.denominator as u64) as i128;
+        let numerator = scaled(self) + scaled(rhs);
+        Ok(RationalNumber {
+            numerator: u32::try_from(numerator.unsigned_abs()).map_err(|_| Error::Overflow)?,
+            denominator: u32::try_from(denominator).map_err(|_| Error::Overflow)?,
+            negative: numerator < 0,
+            format: evaluated_format(self, rhs),
+        })
+    }
+
+    pub fn checked_sub(&self, rhs: &RationalNumber) -> Result<RationalNumber> {
+        self.checked_add(&rhs.neg())
+    }
+
+    pub fn checked_mul(&self, rhs: &RationalNumber) -> Result<RationalNumber> {
+        let numerator = self.numerator as u64 * rhs.numerator as u64;
+        Ok(RationalNumber {
+            numerator: u32::try_from(numerator).map_err(|_| Error::Overflow)?,
+            denominator: u32::try_from(self.denominator as u64 * rhs.denominator as u64).map_err(|_| Error::Overflow)?,
+            negative: numerator != 0 && self.negative != rhs.negative,
+            format: evaluated_format(self, rhs),
+        })
+    }
+
+    pub fn checked_div(&self, rhs: &RationalNumber) -> Result<RationalNumber> {
+        if rhs.numerator == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        }
+        self.checked_mul(&rhs.reciprocal())
+    }
+
+    /// `pow` computed exactly. Fails with `NotRational` when a fractional exponent leaves a root
+    /// that is not a whole number, such as `2^(1/2)`, and with `DenominatorCannotBeZero` for a
+    /// negative power of zero.
+    pub fn checked_pow(&self, exp: &RationalNumber) -> Result<RationalNumber> {
+        let (base, exp) = (self.simplify(), exp.simplify());
+        if base.denominator == 0 || exp.denominator == 0 || (base.numerator == 0 && exp.is_negative()) {
+            return Err(Error::DenominatorCannotBeZero);
+        } else if base.is_negative() && exp.denominator.is_multiple_of(2) {
+            return Err(Error::NotRational);
+        }
+        let power = |n: u32| -> Result<u32> {
+            root(n, exp.denominator)?.checked_pow(exp.numerator).ok_or(Error::Overflow)
+        };
+        let (numerator, denominator) = (power(base.numerator)?, power(base.denominator)?);
+        let result = RationalNumber {
+            numerator,
+            denominator,
+            negative: base.is_negative() && !exp.numerator.is_multiple_of(2),
+            format: evaluated_format(self, &exp),
+        };
+        Ok(if exp.is_negative() { result.reciprocal() } else { result })
+    }
+
     pub fn display_format(&self) -> NumberDisplayFormat {
         self.format
     }
@@ -244,6 +393,35 @@ impl RationalNumber {
         }
     }
 
+    /// `numerator/denominator` as given, without simplifying, or `Overflow` when either part does
+    /// not fit. Whole numbers are shown as decimals and anything else as a fraction.
+    pub(crate) fn from_parts(numerator: i128, denominator: i128) -> Result<RationalNumber> {
+        let format = if denominator == 1 {
+            NumberDisplayFormat::Decimal(None)
+        } else {
+            NumberDisplayFormat::Fraction
+        };
+        Ok(RationalNumber {
+            numerator: u32::try_from(numerator.unsigned_abs()).map_err(|_| Error::Overflow)?,
+            denominator: u32::try_from(denominator).map_err(|_| Error::Overflow)?,
+            negative: numerator < 0,
+            format,
+        })
+    }
+
+    pub(crate) fn signed_numerator(&self) -> i64 {
+        if self.negative {
+            -(self.numerator as i64)
+        } else {
+            self.numerator as i64
+        }
+    }
+
+    // zero is never negative, however its sign is stored
+    fn is_negative(&self) -> bool {
+        self.negative && self.numerator != 0
+    }
+
     pub fn neg(&self) -> RationalNumber {
         RationalNumber {
             negative: !self.negative,
@@ -286,7 +464,7 @@ impl RationalNumber {
         let mut s = format!("{}{}", negative_str, (self.numerator / self.denominator));
         let mut remainder = self.numerator % self.denominator;
         if remainder != 0 {
-            s.push_str(".");
+            s.push('.');
             let mut remainders = Vec::new();
             remainders.push(remainder);
             while remainder != 0 {
@@ -321,27 +499,27 @@ impl RationalNumber {
                 if let Some(place_value) = place_value {
                     let mut s = original_str.clone();
                     let decimal_position = s.chars().position(|c| c == '.').unwrap_or_else(|| {
-                        s.push_str(".");
+                        s.push('.');
                         s.len() - 1
                     });
-                    let digits_needed = (place_value as i64 * -1) - (s.len() - decimal_position - 1) as i64 + 1; // one extra for rounding
+                    let digits_needed = -(place_value as i64) - (s.len() - decimal_position - 1) as i64 + 1; // one extra for rounding
                     if digits_needed > 0 {
                         let digits_needed = digits_needed as usize;
                         if let Some(repeating_digit_count) = repeating_digit_count {
                             for i in 0..digits_needed {
                                 let position = decimal_position + (i % repeating_digit_count) + 1;
                                 let next_digit = original_str.chars().nth(position).expect("failed to get repeating digit");
-                                s.push_str(&next_digit.to_string());
+                                s.push(next_digit);
                             }
                         } else {
                             for _i in 0..digits_needed {
-                                s.push_str("0");
+                                s.push('0');
                             }
                         }
                     }
                     // round if needed
                     let num = f64::from_str(&s).expect("failed to parse float");
-                    return crate::round_f64(num, place_value)
+                    crate::round_f64(num, place_value)
                 } else {
                     if let Some(repeating_digit_count) = repeating_digit_count {
                         let split_index = original_str.len() - repeating_digit_count;
@@ -387,34 +565,74 @@ impl PartialEq for RationalNumber {
     fn eq(&self, other: &Self) -> bool {
         let a = self.simplify();
         let b = other.simplify();
-        a.numerator == b.numerator && a.denominator == b.denominator && a.negative == b.negative
+        a.numerator == b.numerator && a.denominator == b.denominator && a.is_negative() == b.is_negative()
+    }
+}
+
+impl Hash for RationalNumber {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let n = self.simplify();
+        n.numerator.hash(state);
+        n.denominator.hash(state);
+        n.is_negative().hash(state);
     }
 }
 
 impl PartialOrd for RationalNumber {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let ord = if self.negative && !other.negative {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RationalNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.is_negative() && !other.is_negative() {
             std::cmp::Ordering::Less
-        } else if !self.negative && other.negative {
+        } else if !self.is_negative() && other.is_negative() {
             std::cmp::Ordering::Greater
-        } else if self.negative || other.negative {
-            let lcm = crate::lcm(self.denominator, other.denominator);
-            let a = lcm/self.denominator;
-            let b = lcm/other.denominator;
-            (other.numerator * b).cmp(&(self.numerator * a))
+        } else if self.is_negative() {
+            let a = self.numerator as u64 * other.denominator as u64;
+            let b = other.numerator as u64 * self.denominator as u64;
+            b.cmp(&a)
         } else { // both are positive
-            let lcm = crate::lcm(self.denominator, other.denominator);
-            let a = lcm/self.denominator;
-            let b = lcm/other.denominator;
-            (self.numerator * a).cmp(&(other.numerator * b))
-        };
-        Some(ord)
+            let a = self.numerator as u64 * other.denominator as u64;
+            let b = other.numerator as u64 * self.denominator as u64;
+            a.cmp(&b)
+        }
     }
 }
 
 impl From<f32> for RationalNumber {
+    /// The first continued fraction convergent within half an `f32` epsilon of `n`, so `0.1f32`
+    /// is 1/10. Like an `as` cast, NaN becomes 0 and values out of range saturate, where
+    /// `TryFrom<f64>` fails instead.
     fn from(n: f32) -> Self {
-        RationalNumber::parse(&n.to_string()).expect("failed to parse f32")
+        let n = f64::from(n);
+        if n.is_nan() {
+            RationalNumber::from(0)
+        } else if n.abs() > u32::MAX as f64 {
+            let max = RationalNumber::from(u32::MAX);
+            if n < 0.0 {
+                max.neg()
+            } else {
+                max
+            }
+        } else {
+            convergent_within(n, f64::from(f32::EPSILON) / 2.0 * n.abs())
+                .or_else(|_| RationalNumber::approximate(n, u32::MAX))
+                .or_else(|_| RationalNumber::approximate(n, 1))
+                .unwrap_or_else(|_| RationalNumber::from(0))
+        }
+    }
+}
+
+impl TryFrom<f64> for RationalNumber {
+    type Error = Error;
+
+    /// The first continued fraction convergent within a few units in the last place of `n`, so
+    /// `0.1 + 0.2` is 3/10, which fails if that fraction does not fit in a `u32`.
+    fn try_from(n: f64) -> Result<Self> {
+        convergent_within(n, 4.0 * f64::EPSILON * n.abs())
     }
 }
 
@@ -432,7 +650,7 @@ impl From<u32> for RationalNumber {
 impl From<i32> for RationalNumber {
     fn from(n: i32) -> Self {
         RationalNumber {
-            numerator: n.abs() as u32,
+            numerator: n.unsigned_abs(),
             denominator: 1,
             negative: n < 0,
             format: NumberDisplayFormat::Decimal(None),
@@ -556,7 +774,7 @@ impl ops::Sub<RationalNumber> for u32 {
     type Output = RationalNumber;
 
     fn sub(self, rhs: RationalNumber) -> Self::Output {
-        rhs - RationalNumber::from(self)
+        RationalNumber::from(self) - rhs
     }
 }
 
@@ -572,7 +790,7 @@ impl ops::Sub<RationalNumber> for i32 {
     type Output = RationalNumber;
 
     fn sub(self, rhs: RationalNumber) -> Self::Output {
-        rhs - RationalNumber::from(self)
+        RationalNumber::from(self) - rhs
     }
 }
 
@@ -588,7 +806,7 @@ impl ops::Sub<RationalNumber> for f32 {
     type Output = RationalNumber;
 
     fn sub(self, rhs: RationalNumber) -> Self::Output {
-        rhs - RationalNumber::from(self)
+        RationalNumber::from(self) - rhs
     }
 }
 
@@ -684,7 +902,7 @@ impl ops::Div<RationalNumber> for u32 {
     type Output = RationalNumber;
 
     fn div(self, rhs: RationalNumber) -> Self::Output {
-        RationalNumber::from(rhs)/self
+        RationalNumber::from(self)/rhs
     }
 }
 
@@ -700,7 +918,7 @@ impl ops::Div<RationalNumber> for i32 {
     type Output = RationalNumber;
 
     fn div(self, rhs: RationalNumber) -> Self::Output {
-        RationalNumber::from(rhs)/self
+        RationalNumber::from(self)/rhs
     }
 }
 
@@ -716,10 +934,91 @@ impl ops::Div<RationalNumber> for f32 {
     type Output = RationalNumber;
 
     fn div(self, rhs: RationalNumber) -> Self::Output {
-        RationalNumber::from(rhs)/self
+        RationalNumber::from(self)/rhs
     }
 }
 
+// the exact value of a finite float's magnitude as a numerator and a power of two denominator
+fn exact_parts(f: f64) -> (u128, u128) {
+    let (mantissa, exponent) = f64_parts(f);
+    if mantissa == 0 {
+        (0, 1)
+    } else if exponent >= 0 {
+        ((mantissa as u128) << exponent, 1)
+    } else if exponent >= -64 {
+        (mantissa as u128, 1u128 << -exponent)
+    } else {
+        // below 2^-64 the extra bits cannot change the result, so round them away
+        let shift = (-exponent - 64) as u32;
+        let n = if shift >= 64 { 0 } else { ((mantissa >> (shift - 1)) + 1) >> 1 };
+        (n as u128, 1u128 << 64)
+    }
+}
+
+// the first convergent of the exact value of `f` that is within `tolerance` of it
+fn convergent_within(f: f64, tolerance: f64) -> Result<RationalNumber> {
+    if !f.is_finite() {
+        return Err(Error::NotFinite);
+    } else if f.abs() > u32::MAX as f64 {
+        return Err(Error::Overflow);
+    }
+    let (mut n, mut d) = exact_parts(f);
+    let (mut p0, mut q0, mut p1, mut q1) = (0u128, 1u128, 1u128, 0u128);
+    while d != 0 {
+        let a = n / d;
+        let p2 = a.checked_mul(p1).and_then(|p| p.checked_add(p0)).ok_or(Error::Overflow)?;
+        let q2 = a.checked_mul(q1).and_then(|q| q.checked_add(q0)).ok_or(Error::Overflow)?;
+        if p2 > u32::MAX as u128 || q2 > u32::MAX as u128 {
+            return Err(Error::Overflow);
+        }
+        p0 = p1;
+        q0 = q1;
+        p1 = p2;
+        q1 = q2;
+        if (p1 as f64 / q1 as f64 - f.abs()).abs() <= tolerance {
+            break;
+        }
+        let r = n - a * d;
+        n = d;
+        d = r;
+    }
+    Ok(RationalNumber {
+        numerator: p1 as u32,
+        denominator: q1 as u32,
+        negative: f < 0.0 && p1 != 0,
+        format: NumberDisplayFormat::Decimal(None),
+    })
+}
+
+// splits a finite float into an odd mantissa and a power of two exponent
+fn f64_parts(f: f64) -> (u64, i32) {
+    let bits = f.to_bits();
+    let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & 0x000f_ffff_ffff_ffff;
+    let (mantissa, exponent) = if biased_exponent == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | 0x0010_0000_0000_0000, biased_exponent - 1075)
+    };
+    if mantissa == 0 {
+        (0, 0)
+    } else {
+        let zeros = mantissa.trailing_zeros();
+        (mantissa >> zeros, exponent + zeros as i32)
+    }
+}
+
+// the whole number whose `n`th power is `x`
+fn root(x: u32, n: u32) -> Result<u32> {
+    if n == 1 {
+        return Ok(x);
+    }
+    let guess = (x as f64).powf(1.0 / n as f64).round() as u32;
+    (guess.saturating_sub(1)..=guess.saturating_add(1))
+        .find(|r| r.checked_pow(n) == Some(x))
+        .ok_or(Error::NotRational)
+}
+
 fn evaluated_format(a: &RationalNumber, b: &RationalNumber) -> NumberDisplayFormat {
     if a.simplify().denominator == 1 {
         b.format
@@ -731,6 +1030,7 @@ fn evaluated_format(a: &RationalNumber, b: &RationalNumber) -> NumberDisplayForm
 #[cfg(test)]
 mod tests {
     use crate::rational_number::{NumberDisplayFormat, RationalNumber};
+    use std::convert::TryFrom;
     use rand::Rng;
     use std::ops::Neg;
     use crate::PlaceValue;
@@ -773,6 +1073,41 @@ mod tests {
         assert_eq!((a - b).as_f32(), 7.0);
     }
 
+    #[test]
+    fn ignores_the_sign_of_zero() {
+        use std::collections::HashSet;
+        let negative_zero = RationalNumber::new(0, 3, true, NumberDisplayFormat::Fraction);
+        let zero = RationalNumber::from(0);
+        assert_eq!(negative_zero, zero);
+        assert_eq!(negative_zero.cmp(&zero), std::cmp::Ordering::Equal);
+        assert!(negative_zero > RationalNumber::parse("-1/2").unwrap());
+        assert_eq!([negative_zero, zero].iter().collect::<HashSet<_>>().len(), 1);
+    }
+
+    #[test]
+    fn checks_arithmetic() {
+        use crate::Error;
+        let n = |s: &str| RationalNumber::parse(s).unwrap();
+        assert_eq!(n("1/2").checked_add(&n("-1/3")).unwrap().as_str(None), "1/6");
+        assert_eq!(n("1/2").checked_sub(&n("3/4")).unwrap(), n("-1/4"));
+        assert_eq!(n("-2/3").checked_mul(&n("3/4")).unwrap(), n("-1/2"));
+        assert_eq!(n("2/3").checked_div(&n("-4")).unwrap(), n("-1/6"));
+        assert!(matches!(n("100000").checked_mul(&n("100000")), Err(Error::Overflow)));
+        assert!(matches!(n("4000000000").checked_add(&n("4000000000")), Err(Error::Overflow)));
+        assert!(matches!(n("1").checked_div(&n("0")), Err(Error::DenominatorCannotBeZero)));
+
+        assert_eq!(n("2").checked_pow(&n("10")).unwrap(), n("1024"));
+        assert_eq!(n("-2/3").checked_pow(&n("3")).unwrap(), n("-8/27"));
+        assert_eq!(n("2").checked_pow(&n("-2")).unwrap(), n("1/4"));
+        assert_eq!(n("8/27").checked_pow(&n("2/3")).unwrap(), n("4/9"));
+        assert_eq!(n("-8").checked_pow(&n("1/3")).unwrap(), n("-2"));
+        assert_eq!(n("0").checked_pow(&n("0")).unwrap(), n("1"));
+        assert!(matches!(n("2").checked_pow(&n("100")), Err(Error::Overflow)));
+        assert!(matches!(n("0").checked_pow(&n("-1")), Err(Error::DenominatorCannotBeZero)));
+        assert!(matches!(n("2").checked_pow(&n("1/2")), Err(Error::NotRational)));
+        assert!(matches!(n("-4").checked_pow(&n("1/2")), Err(Error::NotRational)));
+    }
+
     #[test]
     fn parses_specific_decimals() {
         assert_eq!(RationalNumber::parse("2.15").unwrap().as_f32(), 2.15);
@@ -831,7 +1166,7 @@ mod tests {
         for _i in 0..1_000 {
             let whole = rng.gen_range(1u32..=10_000u32);
             let denominator = rng.gen_range(2u32..=10_000u32);
-            let numerator = rng.gen_range(1..=denominator);
+            let numerator = rng.gen_range(1..denominator);
 
             let neg_str = if rng.gen_bool(0.5) { "-" } else { "" };
 
@@ -847,14 +1182,14 @@ mod tests {
 
     #[test]
     fn checks_repeating() {
-        assert_eq!(RationalNumber::parse("1/2").unwrap().repeating(), false);
-        assert_eq!(RationalNumber::parse("1/8").unwrap().repeating(), false);
-        assert_eq!(RationalNumber::parse("0").unwrap().repeating(), false);
-        assert_eq!(RationalNumber::parse("4/2").unwrap().repeating(), false);
-        assert_eq!(RationalNumber::parse("4/3").unwrap().repeating(), true);
-        assert_eq!(RationalNumber::parse("1/7").unwrap().repeating(), true);
-        assert_eq!(RationalNumber::parse("14/7").unwrap().repeating(), false);
-        assert_eq!(RationalNumber::parse("1/11").unwrap().repeating(), true);
+        assert!(!RationalNumber::parse("1/2").unwrap().repeating());
+        assert!(!RationalNumber::parse("1/8").unwrap().repeating());
+        assert!(!RationalNumber::parse("0").unwrap().repeating());
+        assert!(!RationalNumber::parse("4/2").unwrap().repeating());
+        assert!(RationalNumber::parse("4/3").unwrap().repeating());
+        assert!(RationalNumber::parse("1/7").unwrap().repeating());
+        assert!(!RationalNumber::parse("14/7").unwrap().repeating());
+        assert!(RationalNumber::parse("1/11").unwrap().repeating());
     }
 
     #[test]
@@ -870,6 +1205,41 @@ mod tests {
         assert_eq!(RationalNumber::parse("1/11").unwrap().as_decimal_str(), ("0.09".to_string(), Some(2)));
     }
 
+    #[test]
+    fn approximates_floats() {
+        assert_eq!(RationalNumber::approximate(0.333333, 100).unwrap().as_str(None), "1/3");
+        assert_eq!(RationalNumber::approximate(std::f64::consts::PI, 1000).unwrap().as_str(None), "355/113");
+        assert_eq!(RationalNumber::approximate(-0.75, 10).unwrap().as_str(None), "-3/4");
+        assert_eq!(RationalNumber::approximate(2.999, 100).unwrap().as_str(None), "3");
+        assert_eq!(RationalNumber::approximate(1e-7, 1000).unwrap().as_str(None), "0");
+        assert_eq!(RationalNumber::approximate(0.5, 1).unwrap().as_str(None), "0");
+        assert!(RationalNumber::approximate(f64::NAN, 100).is_err());
+        assert!(RationalNumber::approximate(f64::INFINITY, 100).is_err());
+        assert!(RationalNumber::approximate(1e20, 100).is_err());
+    }
+
+    #[test]
+    fn converts_floats() {
+        assert_eq!(RationalNumber::try_from(0.1).unwrap().as_str(Some(NumberDisplayFormat::Fraction)), "1/10");
+        assert_eq!(RationalNumber::try_from(1e-7).unwrap().as_str(Some(NumberDisplayFormat::Fraction)), "1/10000000");
+        assert!(RationalNumber::try_from(f64::NAN).is_err());
+        assert!(RationalNumber::try_from(1e-12).is_err());
+        assert_eq!(RationalNumber::try_from(0.1 + 0.2).unwrap().as_str(Some(NumberDisplayFormat::Fraction)), "3/10");
+        assert_eq!(RationalNumber::try_from(-1.0 / 3.0).unwrap().as_str(Some(NumberDisplayFormat::Fraction)), "-1/3");
+        assert!(RationalNumber::try_from(1e10).is_err());
+
+        assert_eq!(RationalNumber::from(0.1f32).as_str(Some(NumberDisplayFormat::Fraction)), "1/10");
+        assert_eq!(RationalNumber::from(2.15f32).as_str(Some(NumberDisplayFormat::Fraction)), "43/20");
+        assert_eq!(RationalNumber::from(f32::NAN), RationalNumber::from(0));
+        assert_eq!(RationalNumber::from(f32::NEG_INFINITY), RationalNumber::from(u32::MAX).neg());
+        assert_eq!(RationalNumber::from(1e-30f32), RationalNumber::from(0));
+
+        assert_eq!(RationalNumber::from_f64_exact(0.375).unwrap().as_str(Some(NumberDisplayFormat::Fraction)), "3/8");
+        assert_eq!(RationalNumber::from_f64_exact(-12.0).unwrap().as_str(None), "-12");
+        assert!(RationalNumber::from_f64_exact(0.1).is_err());
+        assert!(RationalNumber::from_f64_exact(f64::NEG_INFINITY).is_err());
+    }
+
     #[test]
     fn prints_string_decimals() {
         assert_eq!(RationalNumber::parse("1/5").unwrap().as_str(Some(NumberDisplayFormat::Decimal(None))), "0.2");
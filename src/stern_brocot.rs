@@ -0,0 +1,193 @@
+use crate::rational_number::RationalNumber;
+use crate::{Error, Result};
+use std::convert::TryFrom;
+
+const MAX_PATH_LENGTH: i64 = 1000;
+
+impl RationalNumber {
+    /// Adds the numerators and the denominators as written, without simplifying either fraction
+    /// first, so `1/2` and `2/4` give different mediants with the same third fraction.
+    pub fn mediant(&self, other: &RationalNumber) -> Result<RationalNumber> {
+        RationalNumber::from_parts(
+            self.signed_numerator() as i128 + other.signed_numerator() as i128,
+            self.denominator as i128 + other.denominator as i128,
+        )
+    }
+
+    /// Describes the route from `1/1` to this number in the Stern–Brocot tree, one `L` or `R` per
+    /// step. Only positive numbers appear in the tree. Fails with `Overflow` when the route is
+    /// longer than 1000 steps.
+    pub fn stern_brocot_path(&self) -> Result<String> {
+        if self.denominator == 0 {
+            return Err(Error::DenominatorCannotBeZero);
+        } else if self.negative || self.numerator == 0 {
+            return Err(Error::NotPositive);
+        }
+        let terms = self.continued_fraction();
+        if terms.iter().sum::<i64>() - 1 > MAX_PATH_LENGTH {
+            return Err(Error::Overflow);
+        }
+        let last = terms.len() - 1;
+        let mut path = String::new();
+        for (i, a) in terms.iter().enumerate() {
+            let count = if i == last { a - 1 } else { *a };
+            let step = if i % 2 == 0 { 'R' } else { 'L' };
+            for _ in 0..count {
+                path.push(step);
+            }
+        }
+        Ok(path)
+    }
+
+    pub fn from_stern_brocot_path(path: &str) -> Result<Self> {
+        let (mut left_n, mut left_d) = (0i64, 1i64);
+        let (mut right_n, mut right_d) = (1i64, 0i64);
+        for c in path.trim().chars() {
+            let (n, d) = (left_n + right_n, left_d + right_d);
+            if d > u32::MAX as i64 || n > u32::MAX as i64 {
+                return Err(Error::Overflow);
+            }
+            match c {
+                'L' | 'l' => {
+                    right_n = n;
+                    right_d = d;
+                }
+                'R' | 'r' => {
+                    left_n = n;
+                    left_d = d;
+                }
+                _ => return Err(Error::ParseRationalExpression),
+            }
+        }
+        RationalNumber::from_parts((left_n + right_n) as i128, (left_d + right_d) as i128)
+    }
+
+    /// Finds the simplest fraction strictly between `a` and `b`, which is the one with the
+    /// smallest denominator. Returns `None` when that denominator would exceed `max_denominator`.
+    pub fn between(a: &RationalNumber, b: &RationalNumber, max_denominator: u32) -> Option<RationalNumber> {
+        let (lo, hi) = if a < b { (a, b) } else { (b, a) };
+        if lo == hi {
+            None
+        } else if lo.negative && !hi.negative && hi.numerator != 0 {
+            Some(RationalNumber::from(0))
+        } else if lo.negative {
+            simplest_between(&hi.neg(), &lo.neg(), max_denominator).map(|n| n.neg())
+        } else {
+            simplest_between(lo, hi, max_denominator)
+        }
+    }
+}
+
+/// Walks the Stern–Brocot tree, taking runs of steps in the same direction at once.
+fn simplest_between(lo: &RationalNumber, hi: &RationalNumber, max_denominator: u32) -> Option<RationalNumber> {
+    let (lo_n, lo_d) = (lo.numerator as i128, lo.denominator as i128);
+    let (hi_n, hi_d) = (hi.numerator as i128, hi.denominator as i128);
+    let (mut left_n, mut left_d) = (0i128, 1i128);
+    let (mut right_n, mut right_d) = (1i128, 0i128);
+    loop {
+        let (n, d) = (left_n + right_n, left_d + right_d);
+        if d > max_denominator as i128 {
+            return None;
+        }
+        if n * lo_d <= lo_n * d {
+            let k = (lo_n * left_d - left_n * lo_d) / (right_n * lo_d - lo_n * right_d);
+            left_n += k * right_n;
+            left_d += k * right_d;
+        } else if n * hi_d >= hi_n * d {
+            let k = (right_n * hi_d - hi_n * right_d) / (hi_n * left_d - left_n * hi_d);
+            right_n += k * left_n;
+            right_d += k * left_d;
+        } else {
+            return RationalNumber::from_parts(n, d).ok();
+        }
+    }
+}
+
+/// Iterates over the fractions between 0 and 1 whose denominators do not exceed `n`, in
+/// increasing order.
+pub fn farey_sequence(n: u32) -> FareySequence {
+    FareySequence {
+        n,
+        current: if n == 0 { None } else { Some((0, 1)) },
+        next: (1, n),
+    }
+}
+
+pub struct FareySequence {
+    n: u32,
+    current: Option<(u32, u32)>,
+    next: (u32, u32),
+}
+
+impl Iterator for FareySequence {
+    type Item = RationalNumber;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (a, b) = self.current?;
+        let (c, d) = self.next;
+        self.current = if b == 1 && a == 1 {
+            None
+        } else {
+            // every term is at most n, but n + b may not fit in a u32
+            let k = (self.n as u64 + b as u64) / d as u64;
+            self.next = (
+                u32::try_from(k * c as u64 - a as u64).ok()?,
+                u32::try_from(k * d as u64 - b as u64).ok()?,
+            );
+            Some((c, d))
+        };
+        RationalNumber::from_parts(a as i128, b as i128).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rational_number::RationalNumber;
+    use crate::stern_brocot::farey_sequence;
+    use crate::Error;
+
+    #[test]
+    fn finds_fractions_between() {
+        let a = RationalNumber::parse("2/5").unwrap();
+        let b = RationalNumber::parse("3/7").unwrap();
+        assert_eq!(a.mediant(&b).unwrap().as_str(None), "5/12");
+        assert!(RationalNumber::from(u32::MAX).mediant(&RationalNumber::from(1)).is_err());
+        assert_eq!(RationalNumber::between(&a, &b, 100).unwrap().as_str(None), "5/12");
+        assert_eq!(RationalNumber::between(&b, &a, 11), None);
+
+        let a = RationalNumber::parse("1000 1/3").unwrap();
+        let b = RationalNumber::parse("1000 1/2").unwrap();
+        assert_eq!(RationalNumber::between(&a, &b, 100).unwrap().as_str(None), "5002/5");
+
+        let a = RationalNumber::parse("-1/2").unwrap();
+        let b = RationalNumber::parse("-1/3").unwrap();
+        assert_eq!(RationalNumber::between(&a, &b, 100).unwrap().as_str(None), "-2/5");
+        assert_eq!(RationalNumber::between(&a, &RationalNumber::from(3), 100).unwrap().as_str(None), "0");
+        assert_eq!(RationalNumber::between(&a, &a, 100), None);
+    }
+
+    #[test]
+    fn lists_farey_sequences() {
+        let f: Vec<String> = farey_sequence(5).map(|n| n.as_str(None)).collect();
+        assert_eq!(f, vec!["0", "1/5", "1/4", "1/3", "2/5", "1/2", "3/5", "2/3", "3/4", "4/5", "1"]);
+        assert_eq!(farey_sequence(1).count(), 2);
+        assert_eq!(farey_sequence(0).count(), 0);
+        assert_eq!(farey_sequence(u32::MAX).nth(2).unwrap().as_str(None), "1/4294967294");
+    }
+
+    #[test]
+    fn follows_stern_brocot_paths() {
+        let n = RationalNumber::parse("3/7").unwrap();
+        assert_eq!(n.stern_brocot_path().unwrap(), "LLRR");
+        assert_eq!(RationalNumber::from_stern_brocot_path("LLRR").unwrap(), n);
+        assert_eq!(RationalNumber::from(1).stern_brocot_path().unwrap(), "");
+        assert_eq!(RationalNumber::parse("5/2").unwrap().stern_brocot_path().unwrap(), "RRL");
+        assert!(RationalNumber::from(-2).stern_brocot_path().is_err());
+        assert_eq!(RationalNumber::from(1001).stern_brocot_path().unwrap().len(), 1000);
+        assert!(matches!(RationalNumber::from(1002).stern_brocot_path(), Err(Error::Overflow)));
+        assert!(matches!(RationalNumber::from(u32::MAX).stern_brocot_path(), Err(Error::Overflow)));
+        assert!(matches!(RationalNumber::parse("1/4294967295").unwrap().stern_brocot_path(), Err(Error::Overflow)));
+        assert!(RationalNumber::from_stern_brocot_path("LX").is_err());
+        assert!(RationalNumber::parse("5 1/0").unwrap().stern_brocot_path().is_err());
+    }
+}
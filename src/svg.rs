@@ -0,0 +1,111 @@
+// a minimal SVG writer for the crate's diagrams
+
+pub(crate) const FONT: &str = "font-family=\"sans-serif\" font-size=\"14\"";
+
+pub(crate) struct Svg {
+    width: f64,
+    height: f64,
+    elements: Vec<String>,
+}
+
+impl Svg {
+    pub(crate) fn new(width: f64, height: f64) -> Self {
+        Svg {
+            width,
+            height,
+            elements: Vec::new(),
+        }
+    }
+
+    pub(crate) fn line(&mut self, from: (f64, f64), to: (f64, f64), stroke: &str, width: f64) {
+        self.elements.push(format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            number(from.0),
+            number(from.1),
+            number(to.0),
+            number(to.1),
+            stroke,
+            number(width)
+        ));
+    }
+
+    pub(crate) fn circle(&mut self, center: (f64, f64), radius: f64, fill: &str, stroke: &str) {
+        self.elements.push(format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+            number(center.0),
+            number(center.1),
+            number(radius),
+            fill,
+            stroke
+        ));
+    }
+
+    pub(crate) fn rect(&mut self, corner: (f64, f64), size: (f64, f64), fill: &str, stroke: &str) {
+        self.elements.push(format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+            number(corner.0),
+            number(corner.1),
+            number(size.0),
+            number(size.1),
+            fill,
+            stroke
+        ));
+    }
+
+    pub(crate) fn path(&mut self, d: &str, fill: &str, stroke: &str) {
+        self.elements.push(format!("<path d=\"{}\" fill=\"{}\" stroke=\"{}\"/>", d, fill, stroke));
+    }
+
+    /// Centered on `at` horizontally, with `at` on the text's vertical middle.
+    pub(crate) fn text(&mut self, at: (f64, f64), text: &str) {
+        self.push_text(at, escape(text));
+    }
+
+    /// Same as `text`, followed by `overlined` under a bar, as the repeating digits of a decimal.
+    pub(crate) fn overlined_text(&mut self, at: (f64, f64), text: &str, overlined: &str) {
+        self.push_text(
+            at,
+            format!("{}<tspan text-decoration=\"overline\">{}</tspan>", escape(text), escape(overlined)),
+        );
+    }
+
+    fn push_text(&mut self, at: (f64, f64), content: String) {
+        self.elements.push(format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" {}>{}</text>",
+            number(at.0),
+            number(at.1),
+            FONT,
+            content
+        ));
+    }
+
+    pub(crate) fn finish(self) -> String {
+        let mut s = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = number(self.width),
+            h = number(self.height)
+        );
+        for element in self.elements {
+            s.push_str("  ");
+            s.push_str(&element);
+            s.push('\n');
+        }
+        s.push_str("</svg>\n");
+        s
+    }
+}
+
+// coordinates to two decimal places without trailing zeros
+pub(crate) fn number(x: f64) -> String {
+    let s = format!("{:.2}", x);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}